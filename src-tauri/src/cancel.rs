@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use russh::client;
+use russh::ChannelWriteHalf;
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::remote_host::{self, HostPlatform};
+use crate::vmrun::VmrunTarget;
+use crate::{now_ms, SshSession, TraceEntry, TraceStore};
+
+pub(crate) const REQUEST_CANCELLED: &str = "REQUEST_CANCELLED";
+
+/// A remote command currently running for a request.
+struct RunningCommand {
+    // Detached from the request so the cleanup itself cannot be cancelled.
+    session: SshSession,
+    channel: Arc<ChannelWriteHalf<client::Msg>>,
+    command: String,
+}
+
+impl RunningCommand {
+    async fn terminate(self) {
+        let _ = self.channel.close().await;
+        let platform = match self.session.platform {
+            Some(platform) => Ok(platform),
+            None => remote_host::platform_of(&self.session).await,
+        };
+        if let Ok(platform) = platform {
+            let _ = kill_remote_process_tree(&self.session, platform, &self.command).await;
+        }
+    }
+}
+
+struct CancelState {
+    cancelled: watch::Sender<bool>,
+    next_command: AtomicU64,
+    running: Mutex<HashMap<u64, RunningCommand>>,
+}
+
+/// Handle a request's commands use to observe and register for cancellation.
+#[derive(Clone)]
+pub(crate) struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+impl CancelToken {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        *self.state.cancelled.borrow()
+    }
+
+    pub(crate) async fn cancelled(&self) {
+        let mut rx = self.state.cancelled.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// Registers a running remote command so `cancel_request` can close its
+    /// channel and kill what it started. Unregisters when the guard drops.
+    pub(crate) fn track(
+        &self,
+        session: SshSession,
+        channel: Arc<ChannelWriteHalf<client::Msg>>,
+        command: &str,
+    ) -> TrackedCommand {
+        let id = self.state.next_command.fetch_add(1, Ordering::Relaxed);
+        self.state
+            .running
+            .lock()
+            .expect("cancel state poisoned")
+            .insert(
+                id,
+                RunningCommand {
+                    session,
+                    channel,
+                    command: command.to_string(),
+                },
+            );
+        TrackedCommand {
+            state: self.state.clone(),
+            id,
+        }
+    }
+
+    /// Closes and kills every tracked command; safe to call more than once.
+    fn terminate_running(&self) {
+        let running: Vec<RunningCommand> = self
+            .state
+            .running
+            .lock()
+            .expect("cancel state poisoned")
+            .drain()
+            .map(|(_, cmd)| cmd)
+            .collect();
+        for cmd in running {
+            tauri::async_runtime::spawn(cmd.terminate());
+        }
+    }
+}
+
+pub(crate) struct TrackedCommand {
+    state: Arc<CancelState>,
+    id: u64,
+}
+
+impl TrackedCommand {
+    /// For a command that started after the request was already cancelled.
+    pub(crate) fn terminate(self) {
+        CancelToken {
+            state: self.state.clone(),
+        }
+        .terminate_running();
+    }
+}
+
+impl Drop for TrackedCommand {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = self.state.running.lock() {
+            guard.remove(&self.id);
+        }
+    }
+}
+
+/// In-flight requests by `request_id`.
+#[derive(Default)]
+pub(crate) struct CancelRegistry {
+    requests: Mutex<HashMap<String, (Arc<CancelState>, usize)>>,
+}
+
+/// Keeps a request registered while its command runs.
+struct ActiveRequest<'a> {
+    registry: &'a CancelRegistry,
+    request_id: String,
+    token: CancelToken,
+}
+
+impl Drop for ActiveRequest<'_> {
+    fn drop(&mut self) {
+        let mut guard = self
+            .registry
+            .requests
+            .lock()
+            .expect("cancel registry poisoned");
+        if let Some((_, count)) = guard.get_mut(&self.request_id) {
+            *count -= 1;
+            if *count == 0 {
+                guard.remove(&self.request_id);
+            }
+        }
+    }
+}
+
+impl CancelRegistry {
+    fn begin(&self, request_id: &str) -> ActiveRequest<'_> {
+        let mut guard = self.requests.lock().expect("cancel registry poisoned");
+        // Nested commands sharing a request id share one cancellation state.
+        let (state, count) = guard.entry(request_id.to_string()).or_insert_with(|| {
+            (
+                Arc::new(CancelState {
+                    cancelled: watch::channel(false).0,
+                    next_command: AtomicU64::new(0),
+                    running: Mutex::new(HashMap::new()),
+                }),
+                0,
+            )
+        });
+        *count += 1;
+        ActiveRequest {
+            registry: self,
+            request_id: request_id.to_string(),
+            token: CancelToken {
+                state: state.clone(),
+            },
+        }
+    }
+
+    fn cancel(&self, request_id: &str) -> bool {
+        let state = {
+            let guard = self.requests.lock().expect("cancel registry poisoned");
+            match guard.get(request_id) {
+                Some((state, _)) => state.clone(),
+                None => return false,
+            }
+        };
+        state.cancelled.send_replace(true);
+        CancelToken { state }.terminate_running();
+        true
+    }
+}
+
+/// Runs `f` so that `cancel_request(request_id)` aborts it. Requests without
+/// an id cannot be cancelled and run as-is.
+pub(crate) async fn cancellable<T, F, Fut>(
+    app: &AppHandle,
+    action: &str,
+    request_id: &Option<String>,
+    f: F,
+) -> Result<T, String>
+where
+    F: FnOnce(Option<CancelToken>) -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let Some(id) = request_id.as_deref().filter(|id| !id.trim().is_empty()) else {
+        return f(None).await;
+    };
+    let registry = app.state::<CancelRegistry>();
+    let active = registry.begin(id);
+    let token = active.token.clone();
+    let started = Instant::now();
+
+    tokio::select! {
+        biased;
+        res = f(Some(token.clone())) => res,
+        _ = token.cancelled() => {
+            app.state::<TraceStore>().push(TraceEntry {
+                id: 0,
+                at: now_ms(),
+                action: action.to_string(),
+                ok: false,
+                cancelled: true,
+                duration_ms: started.elapsed().as_millis() as u64,
+                command: String::new(),
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_status: None,
+                exit_signal: None,
+                truncated: false,
+                output_id: None,
+                attempts: Vec::new(),
+                error: Some("Cancelled by user".to_string()),
+                request_id: request_id.clone(),
+            });
+            Err(REQUEST_CANCELLED.to_string())
+        }
+    }
+}
+
+/// Kills every host process whose command line carries the command's marker,
+/// children first. Closing the channel alone leaves them running on Windows.
+async fn kill_remote_process_tree(
+    session: &SshSession,
+    platform: HostPlatform,
+    command: &str,
+) -> Result<(), String> {
+    // Killing processes needs no vmrun, so none is configured.
+    let host = platform.host(VmrunTarget::default());
+    let Some(script) = host.kill_process_tree_script(command) else {
+        return Ok(());
+    };
+    session
+        .exec_collect_full(&host.encode(&script))
+        .await
+        .map(|_| ())
+}
+
+#[tauri::command]
+pub(crate) fn cancel_request(
+    registry: tauri::State<'_, CancelRegistry>,
+    request_id: String,
+) -> Result<bool, String> {
+    Ok(registry.cancel(request_id.trim()))
+}
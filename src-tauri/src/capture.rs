@@ -0,0 +1,356 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::decode_remote_output;
+use crate::now_ms;
+use crate::stream::OutputKind;
+
+static CAPTURE_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_OUTPUT: AtomicU64 = AtomicU64::new(0);
+
+const DEFAULT_MAX_IN_MEMORY_BYTES: u64 = 1024 * 1024;
+const MIN_MAX_IN_MEMORY_BYTES: u64 = 16 * 1024;
+const MAX_MAX_IN_MEMORY_BYTES: u64 = 256 * 1024 * 1024;
+const DEFAULT_PAGE_BYTES: u64 = 64 * 1024;
+const MAX_PAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct CaptureSettings {
+    /// Bytes of each stream kept in memory per command; the rest goes to disk.
+    max_in_memory_bytes: u64,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            max_in_memory_bytes: DEFAULT_MAX_IN_MEMORY_BYTES,
+        }
+    }
+}
+
+/// How much of a command's output to hold and where the rest spills.
+#[derive(Debug, Clone)]
+pub(crate) struct CaptureLimits {
+    max_in_memory_bytes: usize,
+    /// Without one, output past the cap is dropped instead of spilled.
+    spill_dir: Option<PathBuf>,
+}
+
+impl Default for CaptureLimits {
+    fn default() -> Self {
+        Self {
+            max_in_memory_bytes: DEFAULT_MAX_IN_MEMORY_BYTES as usize,
+            spill_dir: None,
+        }
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("settings");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir.join("capture.json"))
+}
+
+fn spill_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("output"))
+}
+
+fn load_settings(app: &AppHandle) -> Result<CaptureSettings, String> {
+    let path = settings_path(app)?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(CaptureSettings::default())
+        }
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(CaptureSettings::default());
+    }
+    serde_json::from_str(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_settings(app: &AppHandle, settings: &CaptureSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+pub(crate) fn limits_for(app: &AppHandle) -> Result<CaptureLimits, String> {
+    let _guard = CAPTURE_LOCK.lock().expect("capture lock poisoned");
+    let settings = load_settings(app)?;
+    Ok(CaptureLimits {
+        max_in_memory_bytes: settings.max_in_memory_bytes as usize,
+        spill_dir: Some(spill_dir(app)?),
+    })
+}
+
+/// Spilled output from a previous run is unreachable once the app restarts.
+pub(crate) fn clear_spilled(app: &AppHandle) {
+    if let Ok(dir) = spill_dir(app) {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// Handle shared by every stream a command spills.
+pub(crate) fn next_output_id() -> String {
+    format!(
+        "{}-{}",
+        now_ms(),
+        NEXT_OUTPUT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn stream_suffix(kind: OutputKind) -> &'static str {
+    match kind {
+        OutputKind::Stdout => "stdout",
+        OutputKind::Stderr => "stderr",
+    }
+}
+
+fn spill_path(dir: &Path, output_id: &str, kind: OutputKind) -> PathBuf {
+    dir.join(format!("{output_id}.{}", stream_suffix(kind)))
+}
+
+/// Output ids come back from the frontend; keep them to the shape we issue.
+fn validate_output_id(output_id: &str) -> Result<(), String> {
+    if output_id.is_empty() || !output_id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid output id: {output_id}"));
+    }
+    Ok(())
+}
+
+/// One output stream of one command: the first `max_in_memory_bytes` in
+/// memory and, once that overflows, the whole stream in a spill file.
+pub(crate) struct CaptureBuffer {
+    head: Vec<u8>,
+    max_in_memory_bytes: usize,
+    total_bytes: u64,
+    path: Option<PathBuf>,
+    spill: Option<tokio::fs::File>,
+    spill_failed: bool,
+    /// Set by `finish`; a buffer dropped before that (a command timed out
+    /// or was cancelled mid-read) deletes its partial spill file.
+    finished: bool,
+}
+
+pub(crate) struct Captured {
+    pub(crate) head: Vec<u8>,
+    pub(crate) truncated: bool,
+    /// The spill file holds the complete stream.
+    pub(crate) spilled: bool,
+}
+
+impl CaptureBuffer {
+    pub(crate) fn new(limits: &CaptureLimits, output_id: &str, kind: OutputKind) -> Self {
+        Self {
+            head: Vec::new(),
+            max_in_memory_bytes: limits.max_in_memory_bytes,
+            total_bytes: 0,
+            path: limits
+                .spill_dir
+                .as_deref()
+                .map(|dir| spill_path(dir, output_id, kind)),
+            spill: None,
+            spill_failed: false,
+            finished: false,
+        }
+    }
+
+    pub(crate) async fn push(&mut self, data: &[u8]) {
+        self.total_bytes += data.len() as u64;
+        if let Some(file) = self.spill.as_mut() {
+            if file.write_all(data).await.is_err() {
+                self.spill = None;
+                self.spill_failed = true;
+            }
+            return;
+        }
+
+        let room = self.max_in_memory_bytes.saturating_sub(self.head.len());
+        let (fits, rest) = data.split_at(room.min(data.len()));
+        self.head.extend_from_slice(fits);
+        if rest.is_empty() || self.spill_failed {
+            return;
+        }
+        match self.open_spill(rest).await {
+            Ok(file) => self.spill = Some(file),
+            Err(_) => self.spill_failed = true,
+        }
+    }
+
+    async fn open_spill(&self, rest: &[u8]) -> std::io::Result<tokio::fs::File> {
+        let Some(path) = self.path.as_ref() else {
+            return Err(std::io::Error::other("no spill directory"));
+        };
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut file = tokio::fs::File::create(path).await?;
+        file.write_all(&self.head).await?;
+        file.write_all(rest).await?;
+        Ok(file)
+    }
+
+    pub(crate) async fn finish(mut self) -> Captured {
+        self.finished = true;
+        if let Some(mut file) = self.spill.take() {
+            if file.flush().await.is_err() {
+                self.spill_failed = true;
+            }
+        }
+        if self.spill_failed {
+            if let Some(path) = self.path.as_ref() {
+                // A half-written spill is worse than none.
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+        let truncated = self.total_bytes > self.head.len() as u64;
+        Captured {
+            head: std::mem::take(&mut self.head),
+            truncated,
+            spilled: truncated && !self.spill_failed,
+        }
+    }
+}
+
+impl Drop for CaptureBuffer {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // Close the file first; Windows will not delete an open one.
+        drop(self.spill.take());
+        if let Some(path) = self.path.as_ref() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OutputPage {
+    data: String,
+    offset: u64,
+    next_offset: u64,
+    total_bytes: u64,
+    eof: bool,
+}
+
+#[tauri::command]
+pub(crate) fn capture_settings_get(app: AppHandle) -> Result<CaptureSettings, String> {
+    let _guard = CAPTURE_LOCK.lock().expect("capture lock poisoned");
+    load_settings(&app)
+}
+
+#[tauri::command]
+pub(crate) fn capture_settings_set(
+    app: AppHandle,
+    settings: CaptureSettings,
+) -> Result<(), String> {
+    if !(MIN_MAX_IN_MEMORY_BYTES..=MAX_MAX_IN_MEMORY_BYTES).contains(&settings.max_in_memory_bytes)
+    {
+        return Err(format!(
+            "In-memory output cap must be between {} KiB and {} MiB",
+            MIN_MAX_IN_MEMORY_BYTES / 1024,
+            MAX_MAX_IN_MEMORY_BYTES / (1024 * 1024)
+        ));
+    }
+    let _guard = CAPTURE_LOCK.lock().expect("capture lock poisoned");
+    save_settings(&app, &settings)
+}
+
+/// Reads a page of spilled output starting at byte `offset`.
+#[tauri::command]
+pub(crate) async fn output_read(
+    app: AppHandle,
+    output_id: String,
+    stream: OutputKind,
+    offset: u64,
+    limit: Option<u64>,
+) -> Result<OutputPage, String> {
+    validate_output_id(&output_id)?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE_BYTES).clamp(1, MAX_PAGE_BYTES);
+    let path = spill_path(&spill_dir(&app)?, &output_id, stream);
+    let mut file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|err| format!("Output not found: {output_id} ({err})"))?;
+    let total_bytes = file
+        .metadata()
+        .await
+        .map_err(|err| format!("{err:?}"))?
+        .len();
+    let offset = offset.min(total_bytes);
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+
+    let mut buf = Vec::new();
+    (&mut file)
+        .take(limit)
+        .read_to_end(&mut buf)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let mut eof = offset + buf.len() as u64 >= total_bytes;
+    if !eof {
+        // End pages on a line break so no character is split across pages.
+        if let Some(pos) = buf.iter().rposition(|b| *b == b'\n') {
+            buf.truncate(pos + 1);
+        }
+        eof = offset + buf.len() as u64 >= total_bytes;
+    }
+    Ok(OutputPage {
+        data: decode_remote_output(&buf),
+        offset,
+        next_offset: offset + buf.len() as u64,
+        total_bytes,
+        eof,
+    })
+}
+
+/// Copies the complete spilled stream to `local_path`.
+#[tauri::command]
+pub(crate) async fn output_save(
+    app: AppHandle,
+    output_id: String,
+    stream: OutputKind,
+    local_path: String,
+) -> Result<u64, String> {
+    validate_output_id(&output_id)?;
+    let path = spill_path(&spill_dir(&app)?, &output_id, stream);
+    tokio::fs::copy(&path, &local_path)
+        .await
+        .map_err(|err| format!("Could not save output {output_id} to {local_path}: {err}"))
+}
+
+#[tauri::command]
+pub(crate) async fn output_discard(app: AppHandle, output_id: String) -> Result<(), String> {
+    validate_output_id(&output_id)?;
+    let dir = spill_dir(&app)?;
+    for kind in [OutputKind::Stdout, OutputKind::Stderr] {
+        match tokio::fs::remove_file(spill_path(&dir, &output_id, kind)).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("{err:?}")),
+        }
+    }
+    Ok(())
+}
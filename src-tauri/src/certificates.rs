@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use russh::keys::ssh_key::certificate::CertType;
+use russh::keys::{Certificate, HashAlg, PrivateKey, PublicKey};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::{load_private_key_file, private_key_path_for};
+
+pub(crate) const SSH_CERT_EXPIRED: &str = "SSH_CERT_EXPIRED";
+pub(crate) const SSH_CERT_NOT_YET_VALID: &str = "SSH_CERT_NOT_YET_VALID";
+
+/// Where OpenSSH looks for a key's certificate: `<key>-cert.pub`.
+pub(crate) fn certificate_path(key_path: &Path) -> PathBuf {
+    let mut name = key_path.as_os_str().to_os_string();
+    name.push("-cert.pub");
+    PathBuf::from(name)
+}
+
+/// The certificate stored next to `key_path`, if any.
+pub(crate) fn load_certificate(key_path: &Path) -> Result<Option<Certificate>, String> {
+    let path = certificate_path(key_path);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    Certificate::from_openssh(text.trim())
+        .map(Some)
+        .map_err(|err| format!("Invalid SSH certificate {}: {err}", path.display()))
+}
+
+/// Removes the certificate next to `key_path`; a missing one is fine.
+pub(crate) fn remove_certificate(key_path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(certificate_path(key_path)) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("{err:?}")),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn format_span(secs: u64) -> String {
+    match secs {
+        0..=119 => format!("{secs}s"),
+        120..=7199 => format!("{}m", secs / 60),
+        7200..=172_799 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+fn label(cert: &Certificate) -> String {
+    if cert.key_id().is_empty() {
+        format!("serial {}", cert.serial())
+    } else {
+        format!("'{}'", cert.key_id())
+    }
+}
+
+/// Rejects a certificate outside its validity window, so the user sees why
+/// instead of a generic authentication failure.
+pub(crate) fn check_validity(cert: &Certificate) -> Result<(), String> {
+    let now = now_secs();
+    if now >= cert.valid_before() {
+        return Err(format!(
+            "{SSH_CERT_EXPIRED}: SSH certificate {} expired {} ago; request a new one from your CA",
+            label(cert),
+            format_span(now - cert.valid_before())
+        ));
+    }
+    if now < cert.valid_after() {
+        return Err(format!(
+            "{SSH_CERT_NOT_YET_VALID}: SSH certificate {} becomes valid in {}",
+            label(cert),
+            format_span(cert.valid_after() - now)
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CertificateInfo {
+    key_id: String,
+    serial: u64,
+    /// Empty means valid for any user, as OpenSSH treats it.
+    principals: Vec<String>,
+    valid_after: u64,
+    /// `None` for certificates without an expiry.
+    valid_before: Option<u64>,
+    ca_fingerprint: String,
+    expired: bool,
+}
+
+pub(crate) fn certificate_info(cert: &Certificate) -> CertificateInfo {
+    // Unix seconds in the certificate; milliseconds like every other timestamp here.
+    let to_ms = |secs: u64| secs.saturating_mul(1000);
+    CertificateInfo {
+        key_id: cert.key_id().to_string(),
+        serial: cert.serial(),
+        principals: cert.valid_principals().to_vec(),
+        valid_after: to_ms(cert.valid_after()),
+        valid_before: (cert.valid_before() != u64::MAX).then(|| to_ms(cert.valid_before())),
+        ca_fingerprint: PublicKey::from(cert.signature_key().clone())
+            .fingerprint(HashAlg::Sha256)
+            .to_string(),
+        expired: now_secs() >= cert.valid_before(),
+    }
+}
+
+/// Info for the certificate next to `key_path`, skipping unreadable ones.
+pub(crate) fn certificate_info_for(key_path: &Path) -> Option<CertificateInfo> {
+    load_certificate(key_path)
+        .ok()
+        .flatten()
+        .map(|cert| certificate_info(&cert))
+}
+
+/// Public half of the key at `key_path`; only encrypted non-OpenSSH keys need
+/// to be unlocked first.
+fn key_public_half(app: &AppHandle, key_path: &Path) -> Result<PublicKey, String> {
+    let text = std::fs::read_to_string(key_path).map_err(|err| {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            "SSH private key not configured. Please upload it in the app UI.".to_string()
+        } else {
+            format!("{err:?}")
+        }
+    })?;
+    if let Ok(key) = PrivateKey::from_openssh(text.trim()) {
+        return Ok(key.public_key().clone());
+    }
+    Ok(load_private_key_file(app, key_path)?.public_key().clone())
+}
+
+#[tauri::command]
+pub(crate) fn ssh_certificate_set(
+    app: AppHandle,
+    identity: Option<String>,
+    cert_text: String,
+) -> Result<CertificateInfo, String> {
+    if cert_text.len() > 64 * 1024 {
+        return Err("Certificate too large".to_string());
+    }
+    let cert = Certificate::from_openssh(cert_text.trim())
+        .map_err(|err| format!("Not an OpenSSH certificate: {err}"))?;
+    if cert.cert_type() != CertType::User {
+        return Err("This is a host certificate; a user certificate is required".to_string());
+    }
+
+    let key_path = private_key_path_for(&app, identity.as_deref())?;
+    let public_key = key_public_half(&app, &key_path)?;
+    if cert.public_key() != public_key.key_data() {
+        return Err(format!(
+            "Certificate was issued for a different key (key is {})",
+            public_key.fingerprint(HashAlg::Sha256)
+        ));
+    }
+    check_validity(&cert)?;
+
+    let path = certificate_path(&key_path);
+    let tmp = path.with_extension("pub.tmp");
+    std::fs::write(&tmp, format!("{}\n", cert_text.trim())).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(certificate_info(&cert))
+}
+
+#[tauri::command]
+pub(crate) fn ssh_certificate_clear(
+    app: AppHandle,
+    identity: Option<String>,
+) -> Result<(), String> {
+    remove_certificate(&private_key_path_for(&app, identity.as_deref())?)
+}
+
+#[tauri::command]
+pub(crate) fn ssh_certificate_info(
+    app: AppHandle,
+    identity: Option<String>,
+) -> Result<Option<CertificateInfo>, String> {
+    let key_path = private_key_path_for(&app, identity.as_deref())?;
+    Ok(load_certificate(&key_path)?.map(|cert| certificate_info(&cert)))
+}
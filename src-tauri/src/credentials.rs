@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use tauri::{AppHandle, Manager};
+
+use crate::known_hosts::host_id;
+use crate::SshConfig;
+
+fn ssh_passwords_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("ssh");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir.join("ssh_passwords.json"))
+}
+
+fn credential_key(cfg: &SshConfig) -> String {
+    format!("{}@{}", cfg.user.trim(), host_id(&cfg.host, cfg.port()))
+}
+
+fn load_ssh_passwords(app: &AppHandle) -> Result<HashMap<String, String>, String> {
+    let path = ssh_passwords_path(app)?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str::<HashMap<String, String>>(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_ssh_passwords(app: &AppHandle, map: &HashMap<String, String>) -> Result<(), String> {
+    let path = ssh_passwords_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(map).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+pub(crate) fn get_ssh_password(app: &AppHandle, cfg: &SshConfig) -> Result<Option<String>, String> {
+    Ok(load_ssh_passwords(app)?.remove(&credential_key(cfg)))
+}
+
+#[tauri::command]
+pub(crate) fn ssh_password_status(app: AppHandle, ssh: SshConfig) -> Result<bool, String> {
+    Ok(get_ssh_password(&app, &ssh)?.is_some())
+}
+
+#[tauri::command]
+pub(crate) fn ssh_password_set(
+    app: AppHandle,
+    ssh: SshConfig,
+    password: String,
+) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("SSH password cannot be empty.".to_string());
+    }
+    if password.len() > 4096 {
+        return Err("SSH password too large.".to_string());
+    }
+
+    let mut map = load_ssh_passwords(&app)?;
+    map.insert(credential_key(&ssh), password);
+    save_ssh_passwords(&app, &map)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn ssh_password_clear(app: AppHandle, ssh: SshConfig) -> Result<(), String> {
+    let mut map = load_ssh_passwords(&app)?;
+    map.remove(&credential_key(&ssh));
+    save_ssh_passwords(&app, &map)?;
+    Ok(())
+}
@@ -0,0 +1,609 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use russh::client::{self, AuthResult};
+use russh::keys::{HashAlg, PublicKey};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::known_hosts::host_id;
+use crate::known_hosts::HostKeyVerifier;
+use crate::remote_host::{self, HostPlatform};
+use crate::vmrun::{self, VmrunTarget};
+use crate::{
+    auth, cancel, client_config, dial_jumps, powershell_encoded, powershell_prelude, ssh_config,
+    ssh_credentials, timeouts, CaptureLimits, Client, RequestContext, SshConfig, SshSession,
+};
+
+/// What the server presented during the handshake, captured by `Client`.
+#[derive(Debug, Clone, Default)]
+struct Handshake {
+    banner: Option<String>,
+    kex: Option<String>,
+    cipher: Option<String>,
+    host_key: Option<PublicKey>,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct HandshakeRecorder(Arc<Mutex<Handshake>>);
+
+impl HandshakeRecorder {
+    pub(crate) fn host_key(&self, key: &PublicKey) {
+        self.0.lock().expect("handshake poisoned").host_key = Some(key.clone());
+    }
+
+    pub(crate) fn kex(&self, names: &russh::Names, remote_id: &[u8]) {
+        let mut seen = self.0.lock().expect("handshake poisoned");
+        seen.banner = Some(String::from_utf8_lossy(remote_id).trim().to_string());
+        seen.kex = Some(names.kex.as_ref().to_string());
+        seen.cipher = Some(names.cipher.as_ref().to_string());
+    }
+
+    fn snapshot(&self) -> Handshake {
+        self.0.lock().expect("handshake poisoned").clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Stage {
+    Dns,
+    Tcp,
+    Handshake,
+    AuthMethods,
+    Auth,
+    Shell,
+    Powershell,
+    Vmrun,
+    ScheduledTasks,
+}
+
+const STAGES: [Stage; 9] = [
+    Stage::Dns,
+    Stage::Tcp,
+    Stage::Handshake,
+    Stage::AuthMethods,
+    Stage::Auth,
+    Stage::Shell,
+    Stage::Powershell,
+    Stage::Vmrun,
+    Stage::ScheduledTasks,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StageStatus {
+    Passed,
+    /// Works, but something later may trip over it (e.g. constrained PowerShell).
+    Warning,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StageResult {
+    stage: Stage,
+    status: StageStatus,
+    duration_ms: u64,
+    detail: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HostDiagnosis {
+    host: String,
+    port: u16,
+    user: String,
+    stages: Vec<StageResult>,
+    addresses: Vec<String>,
+    banner: Option<String>,
+    kex: Option<String>,
+    cipher: Option<String>,
+    host_key_algorithm: Option<String>,
+    host_key_fingerprint: Option<String>,
+    auth_methods: Vec<String>,
+    default_shell: Option<String>,
+    platform: Option<HostPlatform>,
+    powershell_version: Option<String>,
+    language_mode: Option<String>,
+    vmrun_path: Option<String>,
+    vmrun_version: Option<String>,
+    scheduled_tasks: Option<bool>,
+}
+
+impl HostDiagnosis {
+    fn record(
+        &mut self,
+        stage: Stage,
+        status: StageStatus,
+        started: Instant,
+        detail: Option<String>,
+        error: Option<String>,
+    ) {
+        self.stages.push(StageResult {
+            stage,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail,
+            error,
+        });
+    }
+
+    fn pass(&mut self, stage: Stage, started: Instant, detail: String) {
+        self.record(stage, StageStatus::Passed, started, Some(detail), None);
+    }
+
+    fn warn(&mut self, stage: Stage, started: Instant, detail: String) {
+        self.record(stage, StageStatus::Warning, started, Some(detail), None);
+    }
+
+    fn fail(&mut self, stage: Stage, started: Instant, error: String) {
+        self.record(stage, StageStatus::Failed, started, None, Some(error));
+    }
+
+    fn skip(&mut self, stage: Stage, reason: &str) {
+        self.stages.push(StageResult {
+            stage,
+            status: StageStatus::Skipped,
+            duration_ms: 0,
+            detail: Some(reason.to_string()),
+            error: None,
+        });
+    }
+
+    fn passed(&self, stage: Stage) -> bool {
+        self.stages.iter().any(|s| {
+            s.stage == stage && matches!(s.status, StageStatus::Passed | StageStatus::Warning)
+        })
+    }
+
+    /// Marks every stage that never ran as skipped, in pipeline order.
+    fn finish(mut self) -> Self {
+        for stage in STAGES {
+            if !self.stages.iter().any(|s| s.stage == stage) {
+                self.skip(stage, "An earlier stage failed");
+            }
+        }
+        self.stages
+            .sort_by_key(|s| STAGES.iter().position(|stage| *stage == s.stage));
+        self
+    }
+}
+
+/// Which shell OpenSSH hands commands to, from the output of `SHELL_PROBE`.
+///
+/// cmd expands `%OS%`, PowerShell expands `$PSVersionTable` and POSIX shells
+/// expand `$0`; each leaves the others' syntax alone.
+const SHELL_PROBE: &str = r#"echo "%OS%|$PSVersionTable|$0""#;
+
+fn classify_shell(output: &str) -> String {
+    let line = output.trim().trim_matches('"');
+    if line.contains("PSVersionHashTable") {
+        return "powershell".to_string();
+    }
+    if line.starts_with("Windows_NT|") {
+        return "cmd".to_string();
+    }
+    match line.strip_prefix("%OS%||") {
+        Some(name) if !name.trim().is_empty() => {
+            let name = name.trim().trim_start_matches('-');
+            name.rsplit('/').next().unwrap_or(name).to_string()
+        }
+        _ => format!("unknown ({})", crate::truncate_text(line, 120)),
+    }
+}
+
+fn output_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+async fn lookup(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<Vec<std::net::SocketAddr>, String> {
+    let addrs = tokio::time::timeout(timeout, tokio::net::lookup_host((host, port)))
+        .await
+        .map_err(|_| format!("Timed out resolving {host} after {}s", timeout.as_secs()))?
+        .map_err(|err| format!("Could not resolve {host}: {err}"))?
+        .collect::<Vec<_>>();
+    if addrs.is_empty() {
+        return Err(format!("{host} resolved to no addresses"));
+    }
+    Ok(addrs)
+}
+
+async fn connect_tcp(
+    addrs: &[std::net::SocketAddr],
+    timeout: Duration,
+) -> Result<tokio::net::TcpStream, String> {
+    let mut errors = Vec::new();
+    for addr in addrs {
+        match tokio::time::timeout(timeout, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(err)) => errors.push(format!("{addr}: {err}")),
+            Err(_) => errors.push(format!("{addr}: timed out after {}s", timeout.as_secs())),
+        }
+    }
+    Err(errors.join("; "))
+}
+
+/// Runs every stage against `cfg` on a fresh, unpooled connection.
+async fn diagnose(
+    app: &AppHandle,
+    ssh: SshConfig,
+    ctx: RequestContext,
+) -> Result<HostDiagnosis, String> {
+    let cfg = ssh_config::resolve_alias(app, &ssh)?;
+    let profile = timeouts::profile_for(app, &cfg.host, cfg.port())?;
+    let timeout = profile.connect_timeout();
+    let mut report = HostDiagnosis {
+        host: cfg.host.clone(),
+        port: cfg.port(),
+        user: cfg.user.clone(),
+        ..Default::default()
+    };
+
+    let verifier = HostKeyVerifier::new(app, &cfg.host, cfg.port())?;
+    let recorder = HandshakeRecorder::default();
+    let handler = Client {
+        host_key: verifier.clone(),
+        handshake: Some(recorder.clone()),
+    };
+    let config = client_config(&profile);
+
+    // Both transports end in the same handshake, but their stream types differ.
+    let mut jumps = Vec::new();
+    let handshake_started;
+    let connected = if cfg.jump_hosts.is_empty() {
+        let started = Instant::now();
+        let addrs = match lookup(&cfg.host, cfg.port(), timeout).await {
+            Ok(addrs) => addrs,
+            Err(err) => {
+                report.fail(Stage::Dns, started, err);
+                return Ok(report.finish());
+            }
+        };
+        report.addresses = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+        report.pass(Stage::Dns, started, report.addresses.join(", "));
+
+        let started = Instant::now();
+        let stream = match connect_tcp(&addrs, timeout).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                report.fail(Stage::Tcp, started, err);
+                return Ok(report.finish());
+            }
+        };
+        let peer = stream
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
+        report.pass(Stage::Tcp, started, format!("Connected to {peer}"));
+
+        handshake_started = Instant::now();
+        tokio::time::timeout(timeout, client::connect_stream(config, stream, handler)).await
+    } else {
+        report.skip(Stage::Dns, "Resolved by the jump host");
+        let started = Instant::now();
+        let route = cfg
+            .jump_hosts
+            .iter()
+            .map(|hop| format!("{}:{}", hop.host, hop.port))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        jumps = match dial_jumps(app, &cfg).await {
+            Ok(jumps) => jumps,
+            Err(err) => {
+                report.fail(Stage::Tcp, started, err);
+                return Ok(report.finish());
+            }
+        };
+        let Some(last) = jumps.last() else {
+            return Err("Jump hosts configured but none connected".to_string());
+        };
+        let channel = match last
+            .channel_open_direct_tcpip(cfg.host.as_str(), u32::from(cfg.port()), "127.0.0.1", 0)
+            .await
+        {
+            Ok(channel) => channel,
+            Err(err) => {
+                report.fail(
+                    Stage::Tcp,
+                    started,
+                    format!("Jump host could not reach the target: {err}"),
+                );
+                return Ok(report.finish());
+            }
+        };
+        report.pass(Stage::Tcp, started, format!("Tunnelled via {route}"));
+
+        handshake_started = Instant::now();
+        tokio::time::timeout(
+            timeout,
+            client::connect_stream(config, channel.into_stream(), handler),
+        )
+        .await
+    };
+
+    let seen = recorder.snapshot();
+    report.banner = seen.banner.clone();
+    report.kex = seen.kex.clone();
+    report.cipher = seen.cipher.clone();
+    if let Some(key) = &seen.host_key {
+        report.host_key_algorithm = Some(key.algorithm().as_str().to_string());
+        report.host_key_fingerprint = Some(key.fingerprint(HashAlg::Sha256).to_string());
+    }
+    let mut session = match connected {
+        Ok(Ok(session)) => {
+            report.pass(
+                Stage::Handshake,
+                handshake_started,
+                format!(
+                    "{}; kex {}; cipher {}; host key {} {}",
+                    seen.banner.as_deref().unwrap_or("no banner"),
+                    report.kex.as_deref().unwrap_or("?"),
+                    report.cipher.as_deref().unwrap_or("?"),
+                    report.host_key_algorithm.as_deref().unwrap_or("?"),
+                    report.host_key_fingerprint.as_deref().unwrap_or("?"),
+                ),
+            );
+            session
+        }
+        Ok(Err(err)) => {
+            let err = match verifier.rejection() {
+                Some(mismatch) => mismatch.to_error(),
+                None => format!("{err:?}"),
+            };
+            report.fail(Stage::Handshake, handshake_started, err);
+            return Ok(report.finish());
+        }
+        Err(_) => {
+            report.fail(
+                Stage::Handshake,
+                handshake_started,
+                format!("Timed out after {}s", timeout.as_secs()),
+            );
+            return Ok(report.finish());
+        }
+    };
+
+    let started = Instant::now();
+    match session.authenticate_none(cfg.user.as_str()).await {
+        Ok(AuthResult::Success) => {
+            report.auth_methods = vec!["none".to_string()];
+            report.pass(Stage::AuthMethods, started, "none".to_string());
+            report.warn(
+                Stage::Auth,
+                started,
+                "Server accepted the user without credentials".to_string(),
+            );
+        }
+        Ok(AuthResult::Failure {
+            remaining_methods, ..
+        }) => {
+            report.auth_methods = remaining_methods
+                .iter()
+                .map(|method| <&str>::from(method).to_string())
+                .collect();
+            report.pass(Stage::AuthMethods, started, report.auth_methods.join(", "));
+
+            let started = Instant::now();
+            let res = match ssh_credentials(app, &cfg) {
+                Ok(creds) => auth::authenticate(&mut session, &cfg.user, &creds).await,
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => report.pass(
+                    Stage::Auth,
+                    started,
+                    format!("Authenticated as {}", cfg.user),
+                ),
+                Err(err) => report.fail(Stage::Auth, started, err),
+            }
+        }
+        Err(err) => report.fail(Stage::AuthMethods, started, format!("{err:?}")),
+    }
+    if !report.passed(Stage::Auth) {
+        return Ok(report.finish());
+    }
+
+    let session = SshSession {
+        session: Arc::new(session),
+        jumps: Arc::new(jumps),
+        profile,
+        capture: CaptureLimits::default(),
+        request: ctx,
+        platform: remote_host::known_platform(app, &ssh),
+    };
+    run_remote_stages(app, &session, &ssh, &mut report).await;
+    let _ = session.close().await;
+    Ok(report.finish())
+}
+
+/// Shell, PowerShell, vmrun and Scheduled Tasks; each reports on its own.
+/// POSIX hosts only get the shell and vmrun stages.
+async fn run_remote_stages(
+    app: &AppHandle,
+    session: &SshSession,
+    ssh: &SshConfig,
+    report: &mut HostDiagnosis,
+) {
+    let started = Instant::now();
+    match session.exec_collect(SHELL_PROBE).await {
+        Ok(out) => {
+            let shell = classify_shell(&out);
+            report.pass(Stage::Shell, started, shell.clone());
+            report.default_shell = Some(shell);
+        }
+        Err(err) => report.fail(Stage::Shell, started, err),
+    }
+    let platform = ssh
+        .platform
+        .unwrap_or(match report.default_shell.as_deref() {
+            Some(shell)
+                if shell != "cmd" && shell != "powershell" && !shell.starts_with("unknown") =>
+            {
+                HostPlatform::Posix
+            }
+            _ => HostPlatform::Windows,
+        });
+    report.platform = Some(platform);
+    if platform == HostPlatform::Posix {
+        report.skip(Stage::Powershell, "Not a Windows host");
+        report.skip(Stage::ScheduledTasks, "Not a Windows host");
+        run_vmrun_stage(app, session, ssh, platform, report).await;
+        return;
+    }
+
+    // No prelude: setting the console encoding is itself blocked in
+    // constrained language mode, which is what this stage looks for.
+    let started = Instant::now();
+    let probe = "$PSVersionTable.PSVersion.ToString();$ExecutionContext.SessionState.LanguageMode.ToString()";
+    match session.exec_collect(&powershell_encoded(probe)).await {
+        Ok(out) => {
+            let lines = output_lines(&out);
+            report.powershell_version = lines.first().cloned();
+            report.language_mode = lines.get(1).cloned();
+            let detail = format!(
+                "PowerShell {} ({})",
+                report.powershell_version.as_deref().unwrap_or("?"),
+                report.language_mode.as_deref().unwrap_or("unknown mode"),
+            );
+            if report.language_mode.as_deref() == Some("FullLanguage") {
+                report.pass(Stage::Powershell, started, detail);
+            } else {
+                report.warn(
+                    Stage::Powershell,
+                    started,
+                    format!("{detail}; VM commands need FullLanguage"),
+                );
+            }
+        }
+        Err(err) => report.fail(Stage::Powershell, started, err),
+    }
+    if !report.passed(Stage::Powershell) {
+        report.skip(Stage::Vmrun, "PowerShell is not available");
+        report.skip(Stage::ScheduledTasks, "PowerShell is not available");
+        return;
+    }
+
+    run_vmrun_stage(app, session, ssh, platform, report).await;
+
+    // Starting VMs registers a temporary task; do the same with a no-op.
+    let started = Instant::now();
+    let script = format!(
+        r#"
+{}
+$tn='tauri-vmdiag-'+[guid]::NewGuid().ToString('N')
+$ac=New-ScheduledTaskAction -Execute 'cmd.exe' -Argument '/c exit 0'
+$tr=New-ScheduledTaskTrigger -Once -At (Get-Date).AddYears(1)
+try {{ Register-ScheduledTask -TaskName $tn -Action $ac -Trigger $tr -Force|Out-Null }}
+finally {{ Unregister-ScheduledTask -TaskName $tn -Confirm:$false -ErrorAction SilentlyContinue|Out-Null }}
+'ok'
+"#,
+        powershell_prelude()
+    );
+    match session.exec_collect(&powershell_encoded(&script)).await {
+        Ok(_) => {
+            report.scheduled_tasks = Some(true);
+            report.pass(
+                Stage::ScheduledTasks,
+                started,
+                "Registered and removed a test task".to_string(),
+            );
+        }
+        Err(err) => {
+            report.scheduled_tasks = Some(false);
+            report.fail(Stage::ScheduledTasks, started, err);
+        }
+    }
+}
+
+/// Locates vmrun the way VM commands do: the configured path, else a fresh
+/// detection, which then replaces the cached one.
+async fn run_vmrun_stage(
+    app: &AppHandle,
+    session: &SshSession,
+    ssh: &SshConfig,
+    platform: HostPlatform,
+    report: &mut HostDiagnosis,
+) {
+    let started = Instant::now();
+    let settings = match vmrun::settings_for(app, &ssh.host, ssh.port()) {
+        Ok(v) => v,
+        Err(err) => {
+            report.fail(Stage::Vmrun, started, err);
+            return;
+        }
+    };
+    let detected = if settings.vmrun_path.is_none() {
+        let finder = platform.host(VmrunTarget::default());
+        let res = session
+            .exec_collect(&finder.encode(&finder.vmrun_detect_script()))
+            .await
+            .and_then(|out| remote_host::parse_detected_vmrun(&out));
+        match res {
+            Ok(detected) => {
+                remote_host::remember_vmrun(app, &host_id(&ssh.host, ssh.port()), &detected);
+                Some(detected)
+            }
+            Err(err) => {
+                report.fail(Stage::Vmrun, started, err);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    let target = settings.target(detected.as_ref());
+    let host_type = target.host_type;
+    let host = platform.host(target);
+    match session
+        .exec_collect(&host.encode(&host.vmrun_probe_script()))
+        .await
+    {
+        Ok(out) => {
+            let lines = output_lines(&out);
+            report.vmrun_path = lines.first().cloned();
+            report.vmrun_version = lines.get(1).cloned();
+            report.pass(
+                Stage::Vmrun,
+                started,
+                format!(
+                    "{} ({}, -T {})",
+                    report.vmrun_path.as_deref().unwrap_or("?"),
+                    report.vmrun_version.as_deref().unwrap_or("unknown version"),
+                    host_type.as_str(),
+                ),
+            );
+        }
+        Err(err) => report.fail(Stage::Vmrun, started, err),
+    }
+}
+
+/// Checks a host stage by stage, from name resolution to Scheduled Tasks.
+/// Failed stages are reported in the result; only setup problems are errors.
+#[tauri::command]
+pub(crate) async fn host_diagnose(
+    app: AppHandle,
+    ssh: SshConfig,
+    request_id: Option<String>,
+) -> Result<HostDiagnosis, String> {
+    cancel::cancellable(&app, "host_diagnose", &request_id, |cancel| {
+        diagnose(
+            &app,
+            ssh,
+            RequestContext {
+                output: None,
+                cancel,
+            },
+        )
+    })
+    .await
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::AppHandle;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+use crate::{now_ms, ssh_connect, SshConfig, SshSession};
+
+#[derive(Default)]
+struct ForwardStats {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+}
+
+struct PortForward {
+    ssh: SshConfig,
+    local_addr: SocketAddr,
+    remote_host: String,
+    remote_port: u16,
+    created_at: u64,
+    stats: Arc<ForwardStats>,
+    shutdown: watch::Sender<bool>,
+    listener: JoinHandle<()>,
+}
+
+impl PortForward {
+    fn stop(&self) {
+        // Connection tasks watch `shutdown`; the accept loop is simply aborted.
+        let _ = self.shutdown.send(true);
+        self.listener.abort();
+    }
+
+    fn info(&self, id: u64) -> PortForwardInfo {
+        PortForwardInfo {
+            id,
+            ssh_host: self.ssh.host.clone(),
+            ssh_port: self.ssh.port(),
+            ssh_user: self.ssh.user.clone(),
+            local_address: self.local_addr.to_string(),
+            remote_host: self.remote_host.clone(),
+            remote_port: self.remote_port,
+            bytes_sent: self.stats.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.stats.bytes_received.load(Ordering::Relaxed),
+            active_connections: self.stats.active_connections.load(Ordering::Relaxed),
+            total_connections: self.stats.total_connections.load(Ordering::Relaxed),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// Local TCP listeners tunnelled to `remote_host:remote_port` through SSH.
+#[derive(Default)]
+pub(crate) struct PortForwards {
+    next_id: AtomicU64,
+    forwards: Mutex<BTreeMap<u64, PortForward>>,
+}
+
+impl PortForwards {
+    /// Stops every forward; called when the app exits.
+    pub(crate) fn close_all(&self) {
+        let mut guard = self.forwards.lock().expect("port forwards poisoned");
+        for forward in guard.values() {
+            forward.stop();
+        }
+        guard.clear();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PortForwardInfo {
+    id: u64,
+    ssh_host: String,
+    ssh_port: u16,
+    ssh_user: String,
+    local_address: String,
+    remote_host: String,
+    remote_port: u16,
+    /// Local client -> remote service.
+    bytes_sent: u64,
+    /// Remote service -> local client.
+    bytes_received: u64,
+    active_connections: u64,
+    total_connections: u64,
+    created_at: u64,
+}
+
+/// Copies one direction until EOF, counting bytes as they pass.
+async fn pump<R, W>(mut reader: R, mut writer: W, counter: &AtomicU64) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0u8; 32 * 1024];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n]).await?;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    writer.shutdown().await
+}
+
+/// Everything an accept loop needs to serve one forward.
+#[derive(Clone)]
+struct ForwardTarget {
+    app: AppHandle,
+    ssh: SshConfig,
+    remote_host: String,
+    remote_port: u16,
+    stats: Arc<ForwardStats>,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl ForwardTarget {
+    async fn serve(self, listener: TcpListener, mut session: SshSession) {
+        loop {
+            let (socket, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(_) => {
+                    // e.g. out of file descriptors; back off instead of spinning.
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            // The forward outlives any single SSH connection; redial if it dropped.
+            if session.session.is_closed() {
+                match ssh_connect(&self.app, &self.ssh).await {
+                    Ok(fresh) => session = fresh,
+                    Err(_) => continue,
+                }
+            }
+
+            self.stats.total_connections.fetch_add(1, Ordering::Relaxed);
+            self.stats
+                .active_connections
+                .fetch_add(1, Ordering::Relaxed);
+            let target = self.clone();
+            let session = session.clone();
+            tauri::async_runtime::spawn(async move {
+                target.forward(session, socket, peer).await;
+                target
+                    .stats
+                    .active_connections
+                    .fetch_sub(1, Ordering::Relaxed);
+            });
+        }
+    }
+
+    async fn forward(&self, session: SshSession, socket: TcpStream, peer: SocketAddr) {
+        let channel = match session
+            .session
+            .channel_open_direct_tcpip(
+                self.remote_host.clone(),
+                u32::from(self.remote_port),
+                peer.ip().to_string(),
+                u32::from(peer.port()),
+            )
+            .await
+        {
+            Ok(channel) => channel,
+            // The client just sees its connection closed, as with `ssh -L`.
+            Err(_) => return,
+        };
+
+        let (local_read, local_write) = socket.into_split();
+        let (remote_read, remote_write) = tokio::io::split(channel.into_stream());
+        let upstream = pump(local_read, remote_write, &self.stats.bytes_sent);
+        let downstream = pump(remote_read, local_write, &self.stats.bytes_received);
+        let mut shutdown = self.shutdown.clone();
+        tokio::select! {
+            _ = async { tokio::join!(upstream, downstream) } => {}
+            _ = shutdown.changed() => {}
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn port_forward_open(
+    app: AppHandle,
+    forwards: tauri::State<'_, PortForwards>,
+    ssh: SshConfig,
+    bind_host: Option<String>,
+    bind_port: u16,
+    remote_host: String,
+    remote_port: u16,
+) -> Result<PortForwardInfo, String> {
+    let remote_host = remote_host.trim().to_string();
+    if remote_host.is_empty() {
+        return Err("Remote host cannot be empty.".to_string());
+    }
+    if remote_port == 0 {
+        return Err("Remote port must be between 1 and 65535.".to_string());
+    }
+    let bind_host = bind_host
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+
+    // Connect first so a bad host fails the command instead of every client.
+    let session = ssh_connect(&app, &ssh).await?;
+    let listener = TcpListener::bind((bind_host.as_str(), bind_port))
+        .await
+        .map_err(|err| format!("Could not listen on {bind_host}:{bind_port}: {err}"))?;
+    let local_addr = listener.local_addr().map_err(|err| format!("{err:?}"))?;
+
+    let stats = Arc::new(ForwardStats::default());
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    let target = ForwardTarget {
+        app: app.clone(),
+        ssh: ssh.clone(),
+        remote_host: remote_host.clone(),
+        remote_port,
+        stats: stats.clone(),
+        shutdown: shutdown_rx,
+    };
+    let listener = tauri::async_runtime::spawn(target.serve(listener, session));
+
+    let forward = PortForward {
+        ssh,
+        local_addr,
+        remote_host,
+        remote_port,
+        created_at: now_ms(),
+        stats,
+        shutdown,
+        listener,
+    };
+    let id = forwards.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    let info = forward.info(id);
+    forwards
+        .forwards
+        .lock()
+        .expect("port forwards poisoned")
+        .insert(id, forward);
+    Ok(info)
+}
+
+#[tauri::command]
+pub(crate) fn port_forward_list(
+    forwards: tauri::State<'_, PortForwards>,
+) -> Result<Vec<PortForwardInfo>, String> {
+    let guard = forwards.forwards.lock().expect("port forwards poisoned");
+    Ok(guard
+        .iter()
+        .map(|(id, forward)| forward.info(*id))
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) fn port_forward_close(
+    forwards: tauri::State<'_, PortForwards>,
+    id: u64,
+) -> Result<(), String> {
+    let forward = forwards
+        .forwards
+        .lock()
+        .expect("port forwards poisoned")
+        .remove(&id)
+        .ok_or_else(|| format!("Port forward not found: {id}"))?;
+    forward.stop();
+    Ok(())
+}
@@ -0,0 +1,425 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use crate::hypervisor::{self, check_uuid, GuestSnapshot, GuestVm, VmProvider};
+use crate::remote_host::HostPlatform;
+use crate::{
+    powershell_prelude, ps_single_quote_escape, ssh_config, timeouts, truncate_text,
+    RequestContext, SshConfig, TraceStore, VmStopMode,
+};
+
+#[derive(Debug, Deserialize)]
+struct HvVm {
+    id: String,
+    name: String,
+    state: String,
+}
+
+impl From<HvVm> for GuestVm {
+    fn from(vm: HvVm) -> Self {
+        GuestVm {
+            provider: VmProvider::Hyperv,
+            is_running: vm.state == "Running",
+            id: vm.id,
+            name: vm.name,
+            state: vm.state,
+        }
+    }
+}
+
+fn require_windows(platform: HostPlatform) -> Result<(), String> {
+    match platform {
+        HostPlatform::Windows => Ok(()),
+        HostPlatform::Posix => Err("Hyper-V requires a Windows host".to_string()),
+    }
+}
+
+/// `body` runs with the VM in `$vm`.
+fn vm_script(platform: HostPlatform, vm_id: &str, body: &str) -> Result<String, String> {
+    require_windows(platform)?;
+    Ok(format!(
+        "{}\n$vm=Get-VM -Id '{}'\n{body}",
+        powershell_prelude(),
+        ps_single_quote_escape(vm_id)
+    ))
+}
+
+/// `ConvertTo-Json` emits `[]`, a bare object for one record or nothing at
+/// all depending on the PowerShell version; all of them are lists here.
+fn parse_records<T: DeserializeOwned>(output: &str) -> Result<Vec<T>, String> {
+    let text = output.trim().trim_start_matches('\u{feff}');
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|err| {
+        format!(
+            "Failed to parse Hyper-V output: {err} (first line: {})",
+            truncate_text(text.lines().next().unwrap_or(""), 240)
+        )
+    })?;
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Null => Vec::new(),
+        item => vec![item],
+    };
+    items
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|err| format!("{err:?}")))
+        .collect()
+}
+
+/// Windows without the Hyper-V role has no `Get-VM`.
+const LIST_SCRIPT: &str = r#"
+if(-not (Get-Command Get-VM -ErrorAction SilentlyContinue)){ throw 'PROVIDER_NOT_INSTALLED: Hyper-V PowerShell module not found' }
+$rows=@(Get-VM | ForEach-Object { [pscustomobject]@{ id=$_.Id.ToString(); name=$_.Name; state=$_.State.ToString() } })
+ConvertTo-Json -Compress -InputObject $rows
+"#;
+
+/// Every VM registered with Hyper-V on the host, running or not.
+#[tauri::command]
+pub(crate) async fn hyperv_list_vms(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestVm>, String> {
+    hypervisor::list_command(&app, "hyperv_list_vms", &request_id, stream, |ctx| {
+        list_vms(&app, &store, &ssh, ctx, &request_id)
+    })
+    .await
+}
+
+pub(crate) async fn list_vms(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<Vec<GuestVm>, String> {
+    let out = hypervisor::run_script_with(
+        app,
+        store,
+        ssh,
+        ctx,
+        "hyperv_list_vms",
+        request_id,
+        true,
+        |platform| {
+            require_windows(platform)?;
+            Ok(format!("{}\n{LIST_SCRIPT}", powershell_prelude()))
+        },
+    )
+    .await?;
+    Ok(parse_records::<HvVm>(&out)?
+        .into_iter()
+        .map(GuestVm::from)
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) async fn hyperv_start_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_start_vm",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            vm_script(
+                platform,
+                &vm_id,
+                "Start-VM -VM $vm\n\"$($vm.Name): $((Get-VM -Id $vm.Id).State)\"",
+            )
+        },
+    )
+    .await
+}
+
+/// Soft asks the guest OS to shut down (`-Force` skips the prompt about
+/// unsaved work); hard turns the VM off.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn hyperv_stop_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    mode: Option<VmStopMode>,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    let cfg = ssh_config::resolve_alias(&app, &ssh)?;
+    let polls = timeouts::profile_for(&app, &cfg.host, cfg.port())?.stop_poll_budget;
+    // A bare soft Stop-VM blocks until the guest powers off, however long.
+    let stop = match mode.unwrap_or(VmStopMode::Soft) {
+        VmStopMode::Soft => format!(
+            r#"$job=Stop-VM -VM $vm -Force -AsJob
+Wait-Job -Job $job -Timeout {polls} | Out-Null
+if($job.State -eq 'Failed'){{ Receive-Job -Job $job }}
+if((Get-VM -Id $vm.Id).State -ne 'Off'){{
+  [Console]::Error.WriteLine('VM is still running after shutdown')
+  exit 1
+}}"#
+        ),
+        VmStopMode::Hard => "Stop-VM -VM $vm -TurnOff -Force".to_string(),
+    };
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_stop_vm",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            vm_script(
+                platform,
+                &vm_id,
+                &format!("{stop}\n\"$($vm.Name): $((Get-VM -Id $vm.Id).State)\""),
+            )
+        },
+    )
+    .await
+}
+
+const CHECKPOINT_LIST_SCRIPT: &str = r#"
+$rows=@(Get-VMSnapshot -VM $vm | ForEach-Object {
+  [pscustomobject]@{
+    id=$_.Id.ToString()
+    name=$_.Name
+    parentId=if($_.ParentSnapshotId){ $_.ParentSnapshotId.ToString() }else{ $null }
+    createdAt=([DateTimeOffset]$_.CreationTime).ToUnixTimeMilliseconds()
+    isCurrent=($vm.ParentSnapshotId -eq $_.Id)
+  }
+})
+ConvertTo-Json -Compress -InputObject $rows
+"#;
+
+#[tauri::command]
+pub(crate) async fn hyperv_checkpoint_list(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestSnapshot>, String> {
+    check_uuid("VM id", &vm_id)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_checkpoint_list",
+        request_id,
+        stream,
+        true,
+        |platform| vm_script(platform, &vm_id, CHECKPOINT_LIST_SCRIPT),
+    )
+    .await?;
+    parse_records(&out)
+}
+
+/// Returns the new checkpoint's id.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn hyperv_checkpoint_create(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    hypervisor::check_name("Checkpoint name", &name)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_checkpoint_create",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            vm_script(
+                platform,
+                &vm_id,
+                &format!(
+                    "(Checkpoint-VM -VM $vm -SnapshotName '{}' -Passthru).Id.ToString()",
+                    ps_single_quote_escape(name.trim())
+                ),
+            )
+        },
+    )
+    .await?;
+    Ok(out.trim().to_string())
+}
+
+/// `body` runs with the VM in `$vm` and the checkpoint in `$cp`.
+fn checkpoint_script(
+    platform: HostPlatform,
+    vm_id: &str,
+    checkpoint_id: &str,
+    body: &str,
+) -> Result<String, String> {
+    vm_script(
+        platform,
+        vm_id,
+        &format!(
+            "$cp=Get-VMSnapshot -VM $vm | Where-Object {{ $_.Id -eq '{id}' }}\nif(-not $cp){{ throw 'Checkpoint {id} not found' }}\n{body}",
+            id = ps_single_quote_escape(checkpoint_id)
+        ),
+    )
+}
+
+/// The VM must be off or saved; Hyper-V refuses to restore a running one.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn hyperv_checkpoint_restore(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    checkpoint_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    check_uuid("Checkpoint id", &checkpoint_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_checkpoint_restore",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            checkpoint_script(
+                platform,
+                &vm_id,
+                &checkpoint_id,
+                "Restore-VMSnapshot -VMSnapshot $cp -Confirm:$false\n\"Restored $($cp.Name)\"",
+            )
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn hyperv_checkpoint_delete(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    checkpoint_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    check_uuid("Checkpoint id", &checkpoint_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "hyperv_checkpoint_delete",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            checkpoint_script(
+                platform,
+                &vm_id,
+                &checkpoint_id,
+                "Remove-VMSnapshot -VMSnapshot $cp -Confirm:$false\n\"Deleted $($cp.Name)\"",
+            )
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `LIST_SCRIPT` on Windows Server 2022 (PowerShell 5.1), which prefixes
+    /// a BOM and ends with CRLF.
+    const GET_VM: &str = "\u{feff}[{\"id\":\"5a0b6c1e-2f43-4d1a-9b7e-3c8d2e6f1a04\",\"name\":\"DC01\",\"state\":\"Running\"},{\"id\":\"c8e2f7a9-61b0-4e3d-8a55-0f9d4b2c7e18\",\"name\":\"Build Agent\",\"state\":\"Off\"},{\"id\":\"0d7f3b2a-9c4e-4f61-b8a2-6e1c5d9f0b37\",\"name\":\"SQL\",\"state\":\"Saved\"}]\r\n";
+
+    /// `CHECKPOINT_LIST_SCRIPT` for a VM with a root checkpoint and the one
+    /// it currently runs from.
+    const GET_VM_SNAPSHOT: &str = "[{\"id\":\"7f1c2d3e-4a5b-4c6d-8e9f-0a1b2c3d4e5f\",\"name\":\"Before patching\",\"parentId\":null,\"createdAt\":1717401600000,\"isCurrent\":false},{\"id\":\"9e8d7c6b-5a4f-4e3d-9c2b-1a0f9e8d7c6b\",\"name\":\"Patched\",\"parentId\":\"7f1c2d3e-4a5b-4c6d-8e9f-0a1b2c3d4e5f\",\"createdAt\":1717488000000,\"isCurrent\":true}]\r\n";
+
+    #[test]
+    fn parses_get_vm_list() {
+        let vms = parse_records::<HvVm>(GET_VM)
+            .unwrap()
+            .into_iter()
+            .map(GuestVm::from)
+            .collect::<Vec<_>>();
+        assert_eq!(vms.len(), 3);
+        assert_eq!(vms[0].provider, VmProvider::Hyperv);
+        assert_eq!(vms[0].id, "5a0b6c1e-2f43-4d1a-9b7e-3c8d2e6f1a04");
+        assert_eq!(vms[0].name, "DC01");
+        assert!(vms[0].is_running);
+        assert_eq!(vms[1].name, "Build Agent");
+        assert!(!vms[1].is_running);
+        assert_eq!(vms[2].state, "Saved");
+        assert!(!vms[2].is_running);
+    }
+
+    #[test]
+    fn single_record_is_a_bare_object() {
+        let out = "{\"id\":\"5a0b6c1e-2f43-4d1a-9b7e-3c8d2e6f1a04\",\"name\":\"DC01\",\"state\":\"Paused\"}\r\n";
+        let vms = parse_records::<HvVm>(out).unwrap();
+        assert_eq!(vms.len(), 1);
+        let vm = GuestVm::from(vms.into_iter().next().unwrap());
+        assert_eq!(vm.state, "Paused");
+        assert!(!vm.is_running);
+    }
+
+    #[test]
+    fn empty_outputs_are_no_records() {
+        for out in ["", "\r\n", "[]\r\n", "null", "\u{feff}\r\n"] {
+            assert!(parse_records::<HvVm>(out).unwrap().is_empty(), "{out:?}");
+        }
+    }
+
+    #[test]
+    fn parses_get_vm_snapshot() {
+        let snapshots = parse_records::<GuestSnapshot>(GET_VM_SNAPSHOT).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].name, "Before patching");
+        assert_eq!(snapshots[0].parent_id, None);
+        assert_eq!(snapshots[0].created_at, Some(1717401600000));
+        assert!(!snapshots[0].is_current);
+        assert_eq!(
+            snapshots[1].parent_id.as_deref(),
+            Some("7f1c2d3e-4a5b-4c6d-8e9f-0a1b2c3d4e5f")
+        );
+        assert!(snapshots[1].is_current);
+    }
+
+    #[test]
+    fn malformed_output_is_an_error() {
+        let err = parse_records::<HvVm>("Get-VM : You do not have the required permission\r\n")
+            .unwrap_err();
+        assert!(err.contains("Failed to parse Hyper-V output"), "{err}");
+        assert!(parse_records::<HvVm>("[{\"id\":\"x\"}]").is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use std::future::Future;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::remote_host::{self, HostPlatform};
+use crate::stream::OutputStream;
+use crate::{
+    cancel, hyperv, libvirt, now_ms, proxmox, retry, ssh_connect, truncate_text, virtualbox,
+    vmware_list_running_inner, ExecCollected, RequestContext, SshConfig, TraceEntry, TraceStore,
+};
+
+/// Prefix on errors from a host without the provider's tools; scripts print
+/// it verbatim.
+pub(crate) const PROVIDER_NOT_INSTALLED: &str = "PROVIDER_NOT_INSTALLED";
+
+/// Prefix on errors from a host whose provider is installed but whose
+/// service the SSH user cannot reach (not running, or no permission).
+pub(crate) const PROVIDER_UNAVAILABLE: &str = "PROVIDER_UNAVAILABLE";
+
+/// Which hypervisor manages a VM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum VmProvider {
+    Vmware,
+    Hyperv,
+    Virtualbox,
+    Libvirt,
+    Proxmox,
+}
+
+/// A VM as any provider reports it, alongside VMware's `VmItem`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GuestVm {
+    pub(crate) provider: VmProvider,
+    /// What the provider's commands take: a VMX path, Hyper-V VM id or
+    /// VirtualBox / libvirt UUID or Proxmox VMID.
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) is_running: bool,
+    /// The provider's own state name, e.g. `Running`, `Saved`, `Paused`.
+    pub(crate) state: String,
+}
+
+/// A checkpoint / snapshot of a VM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GuestSnapshot {
+    /// What the provider's snapshot commands take; libvirt and Proxmox
+    /// snapshots have only a name, so for them this is the name.
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) parent_id: Option<String>,
+    /// Unix milliseconds, when the provider reports it.
+    pub(crate) created_at: Option<u64>,
+    /// The snapshot the VM currently runs from.
+    pub(crate) is_current: bool,
+}
+
+/// Runs one provider command and traces it like the VMware commands do.
+/// `retry` is for idempotent commands (listing, status); state changes run
+/// once on a fresh session.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec_traced(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+    action: &str,
+    command_log: &str,
+    exec_command: &str,
+    request_id: &Option<String>,
+    retry: bool,
+) -> Result<ExecCollected, String> {
+    let started = Instant::now();
+    let (res, attempts) = if retry {
+        retry::exec_with_retry(
+            app,
+            store,
+            ssh,
+            ctx,
+            action,
+            command_log,
+            exec_command,
+            request_id,
+        )
+        .await?
+    } else {
+        let session = ssh_connect(app, ssh).await?.with_request(ctx.clone());
+        (session.exec_collect_full(exec_command).await?, Vec::new())
+    };
+
+    let ok = res.success();
+    store.push(TraceEntry {
+        id: 0,
+        at: now_ms(),
+        action: action.to_string(),
+        ok,
+        cancelled: false,
+        duration_ms: started.elapsed().as_millis() as u64,
+        command: truncate_text(command_log.trim(), 16 * 1024),
+        stdout: truncate_text(&res.stdout, 64 * 1024),
+        stderr: truncate_text(&res.stderr, 64 * 1024),
+        exit_status: res.exit_status,
+        exit_signal: res.exit_signal.clone(),
+        truncated: res.truncated,
+        output_id: res.output_id.clone(),
+        attempts,
+        error: if ok {
+            None
+        } else {
+            Some(truncate_text(&res.error_message(), 8 * 1024))
+        },
+        request_id: request_id.clone(),
+    });
+
+    if ok {
+        Ok(res)
+    } else {
+        Err(res.error_message())
+    }
+}
+
+/// Runs a provider's `list_vms` as a command of its own, with the request's
+/// cancel token and output stream.
+pub(crate) async fn list_command<T, F, Fut>(
+    app: &AppHandle,
+    action: &str,
+    request_id: &Option<String>,
+    stream: Option<bool>,
+    list: F,
+) -> Result<Vec<T>, String>
+where
+    F: FnOnce(RequestContext) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, String>>,
+{
+    let output = OutputStream::for_request(app, stream, request_id)?;
+    cancel::cancellable(app, action, request_id, |cancel| {
+        list(RequestContext { output, cancel })
+    })
+    .await
+}
+
+/// Runs the script `script` builds for the host's platform, traced under
+/// `action`, with the request's cancel token and output stream. Returns stdout.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_script<F>(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    action: &str,
+    request_id: Option<String>,
+    stream: Option<bool>,
+    retry: bool,
+    script: F,
+) -> Result<String, String>
+where
+    F: FnOnce(HostPlatform) -> Result<String, String>,
+{
+    let output = OutputStream::for_request(app, stream, &request_id)?;
+    cancel::cancellable(app, action, &request_id, |cancel| {
+        run_script_with(
+            app,
+            store,
+            ssh,
+            RequestContext { output, cancel },
+            action,
+            &request_id,
+            retry,
+            script,
+        )
+    })
+    .await
+}
+
+/// `run_script` inside a request that is already registered.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_script_with<F>(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+    action: &str,
+    request_id: &Option<String>,
+    retry: bool,
+    script: F,
+) -> Result<String, String>
+where
+    F: FnOnce(HostPlatform) -> Result<String, String>,
+{
+    let platform = remote_host::platform_for(app, ssh, &ctx).await?;
+    let script = script(platform)?;
+    let res = exec_traced(
+        app,
+        store,
+        ssh,
+        &ctx,
+        action,
+        &script,
+        &platform.encode(&script),
+        request_id,
+        retry,
+    )
+    .await?;
+    Ok(res.stdout)
+}
+
+/// Provider ids that are GUIDs (Hyper-V, VirtualBox, libvirt); checked up
+/// front so a typo fails here rather than as a confusing host error.
+pub(crate) fn check_uuid(label: &str, id: &str) -> Result<(), String> {
+    let valid = id.len() == 36
+        && id.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("{label} is not a UUID: {id}"))
+    }
+}
+
+/// Rejects names that would break out of a quoted script argument.
+pub(crate) fn check_name(label: &str, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(format!("{label} must not be empty"));
+    }
+    if name.contains('"') || name.contains('\n') || name.contains('\r') || name.contains('\0') {
+        return Err(format!("{label} contains unsupported characters"));
+    }
+    Ok(())
+}
+
+/// Errors from a host without the provider, or without one the SSH user can
+/// use, which contributes no VMs rather than an error.
+fn is_missing(err: &str) -> bool {
+    err.contains(PROVIDER_NOT_INSTALLED)
+        || err.contains(PROVIDER_UNAVAILABLE)
+        || err.contains(proxmox::SUDO_PASSWORD_REQUIRED)
+}
+
+/// File name of a VMX path without `.vmx`, on either kind of host.
+fn vmx_name(vmx_path: &str) -> String {
+    let file = vmx_path.rsplit(['/', '\\']).next().unwrap_or(vmx_path);
+    match file.len().checked_sub(4) {
+        Some(end) if file.is_char_boundary(end) && file[end..].eq_ignore_ascii_case(".vmx") => {
+            file[..end].to_string()
+        }
+        _ => file.to_string(),
+    }
+}
+
+/// Why one provider's VMs are missing from `VmStatusAll`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProviderError {
+    pub(crate) provider: VmProvider,
+    pub(crate) error: String,
+}
+
+/// Every VM the providers that answered reported, and why the others did
+/// not; one failing provider does not hide the rest.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VmStatusAll {
+    pub(crate) vms: Vec<GuestVm>,
+    pub(crate) errors: Vec<ProviderError>,
+}
+
+impl VmStatusAll {
+    fn add(&mut self, provider: VmProvider, res: Result<Vec<GuestVm>, String>) {
+        match res {
+            Ok(vms) => self.vms.extend(vms),
+            Err(err) if is_missing(&err) => {}
+            Err(error) => self.errors.push(ProviderError { provider, error }),
+        }
+    }
+}
+
+/// The known VMware VMs plus every VM the host's other providers report.
+#[tauri::command]
+pub(crate) async fn vm_status_all(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    known_vmx_paths: Vec<String>,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<VmStatusAll, String> {
+    let output = OutputStream::for_request(&app, stream, &request_id)?;
+    cancel::cancellable(&app, "vm_status_all", &request_id, |cancel| {
+        status_all(
+            &app,
+            &store,
+            &ssh,
+            known_vmx_paths,
+            RequestContext { output, cancel },
+            &request_id,
+        )
+    })
+    .await
+}
+
+async fn vmware_status(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    known_vmx_paths: Vec<String>,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<Vec<GuestVm>, String> {
+    let running =
+        vmware_list_running_inner(app, store, ssh.clone(), request_id.clone(), ctx.clone()).await?;
+    let host = remote_host::host_for(app, ssh, &ctx).await?;
+    Ok(known_vmx_paths
+        .into_iter()
+        .map(|vmx_path| {
+            let is_running = running.iter().any(|p| host.same_path(p, &vmx_path));
+            GuestVm {
+                provider: VmProvider::Vmware,
+                name: vmx_name(&vmx_path),
+                id: vmx_path,
+                is_running,
+                state: if is_running { "running" } else { "stopped" }.to_string(),
+            }
+        })
+        .collect())
+}
+
+async fn status_all(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    known_vmx_paths: Vec<String>,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<VmStatusAll, String> {
+    let mut all = VmStatusAll::default();
+    if !known_vmx_paths.is_empty() {
+        all.add(
+            VmProvider::Vmware,
+            vmware_status(app, store, ssh, known_vmx_paths, ctx.clone(), request_id).await,
+        );
+    }
+
+    match remote_host::platform_for(app, ssh, &ctx).await? {
+        HostPlatform::Windows => all.add(
+            VmProvider::Hyperv,
+            hyperv::list_vms(app, store, ssh, ctx.clone(), request_id).await,
+        ),
+        HostPlatform::Posix => {
+            all.add(
+                VmProvider::Libvirt,
+                libvirt::list_vms(app, store, ssh, ctx.clone(), request_id).await,
+            );
+            all.add(
+                VmProvider::Proxmox,
+                proxmox::list_vms(app, store, ssh, ctx.clone(), request_id)
+                    .await
+                    .map(|vms| vms.into_iter().map(GuestVm::from).collect()),
+            );
+        }
+    }
+    all.add(
+        VmProvider::Virtualbox,
+        virtualbox::list_vms(app, store, ssh, ctx, request_id).await,
+    );
+    Ok(all)
+}
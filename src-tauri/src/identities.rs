@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use russh::keys::{HashAlg, PrivateKey, PublicKey};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::certificates::{self, CertificateInfo};
+use crate::{decode_ssh_private_key, now_ms, ssh_key_is_encrypted, KeyPassphraseCache};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdentityEntry {
+    file: String,
+    algorithm: String,
+    fingerprint: String,
+    comment: String,
+    encrypted: bool,
+    added_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SshIdentity {
+    name: String,
+    algorithm: String,
+    fingerprint: String,
+    comment: String,
+    encrypted: bool,
+    unlocked: bool,
+    certificate: Option<CertificateInfo>,
+    added_at: u64,
+}
+
+fn identities_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("ssh")
+        .join("identities");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir)
+}
+
+fn identities_index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(identities_dir(app)?.join("index.json"))
+}
+
+fn load_identities(app: &AppHandle) -> Result<BTreeMap<String, IdentityEntry>, String> {
+    let path = identities_index_path(app)?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    serde_json::from_str(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_identities(app: &AppHandle, map: &BTreeMap<String, IdentityEntry>) -> Result<(), String> {
+    let path = identities_index_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(map).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+fn validate_identity_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Identity name cannot be empty.".to_string());
+    }
+    if name.len() > 128 {
+        return Err("Identity name too long.".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Public half of a stored key. OpenSSH keys carry it unencrypted, so this
+/// works for locked keys too; other formats need the decrypted key.
+fn public_key_of(key_text: &str, decrypted: Option<&PrivateKey>) -> Option<PublicKey> {
+    if let Some(key) = decrypted {
+        return Some(key.public_key().clone());
+    }
+    PrivateKey::from_openssh(key_text.trim())
+        .ok()
+        .map(|key| key.public_key().clone())
+}
+
+fn to_identity(app: &AppHandle, name: String, entry: IdentityEntry) -> SshIdentity {
+    let key_path = identities_dir(app).map(|dir| dir.join(&entry.file)).ok();
+    let unlocked = !entry.encrypted
+        || key_path
+            .as_ref()
+            .is_some_and(|path| app.state::<KeyPassphraseCache>().get(path).is_some());
+    let certificate = key_path
+        .as_deref()
+        .and_then(certificates::certificate_info_for);
+    SshIdentity {
+        name,
+        algorithm: entry.algorithm,
+        fingerprint: entry.fingerprint,
+        comment: entry.comment,
+        encrypted: entry.encrypted,
+        unlocked,
+        certificate,
+        added_at: entry.added_at,
+    }
+}
+
+/// Key file backing the named identity.
+pub(crate) fn identity_key_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let map = load_identities(app)?;
+    let entry = map
+        .get(name.trim())
+        .ok_or_else(|| format!("SSH identity not found: {}", name.trim()))?;
+    Ok(identities_dir(app)?.join(&entry.file))
+}
+
+#[tauri::command]
+pub(crate) fn ssh_identity_list(app: AppHandle) -> Result<Vec<SshIdentity>, String> {
+    Ok(load_identities(&app)?
+        .into_iter()
+        .map(|(name, entry)| to_identity(&app, name, entry))
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) fn ssh_identity_add(
+    app: AppHandle,
+    cache: tauri::State<'_, KeyPassphraseCache>,
+    name: String,
+    key_text: String,
+    passphrase: Option<String>,
+    comment: Option<String>,
+) -> Result<SshIdentity, String> {
+    let name = validate_identity_name(&name)?;
+    if key_text.len() > 256 * 1024 {
+        return Err("Key too large".to_string());
+    }
+
+    let mut map = load_identities(&app)?;
+    if map.contains_key(&name) {
+        return Err(format!("SSH identity already exists: {name}"));
+    }
+
+    let encrypted = ssh_key_is_encrypted(&key_text);
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let decrypted = if !encrypted || passphrase.is_some() {
+        Some(decode_ssh_private_key(&key_text, passphrase.as_deref())?)
+    } else {
+        None
+    };
+    let public_key = public_key_of(&key_text, decrypted.as_ref()).ok_or_else(|| {
+        "Passphrase required to read this key's fingerprint (only OpenSSH keys expose it while locked)"
+            .to_string()
+    })?;
+
+    let comment = comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .or_else(|| {
+            decrypted
+                .as_ref()
+                .map(|key| key.comment().to_string())
+                .filter(|c| !c.is_empty())
+        })
+        .unwrap_or_else(|| public_key.comment().to_string());
+
+    let dir = identities_dir(&app)?;
+    let mut file = format!("key_{}", now_ms());
+    while dir.join(&file).exists() {
+        file.push('_');
+    }
+    let key_path = dir.join(&file);
+    std::fs::write(&key_path, &key_text).map_err(|err| format!("{err:?}"))?;
+
+    if let (true, Some(passphrase)) = (encrypted, passphrase) {
+        cache.set(&key_path, passphrase);
+    }
+
+    let entry = IdentityEntry {
+        file,
+        algorithm: public_key.algorithm().as_str().to_string(),
+        fingerprint: public_key.fingerprint(HashAlg::Sha256).to_string(),
+        comment,
+        encrypted,
+        added_at: now_ms(),
+    };
+    map.insert(name.clone(), entry.clone());
+    if let Err(err) = save_identities(&app, &map) {
+        let _ = std::fs::remove_file(&key_path);
+        return Err(err);
+    }
+    Ok(to_identity(&app, name, entry))
+}
+
+#[tauri::command]
+pub(crate) fn ssh_identity_remove(
+    app: AppHandle,
+    cache: tauri::State<'_, KeyPassphraseCache>,
+    name: String,
+) -> Result<(), String> {
+    let mut map = load_identities(&app)?;
+    let Some(entry) = map.remove(name.trim()) else {
+        return Ok(());
+    };
+    save_identities(&app, &map)?;
+
+    let key_path = identities_dir(&app)?.join(&entry.file);
+    cache.forget(&key_path);
+    certificates::remove_certificate(&key_path)?;
+    match std::fs::remove_file(&key_path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("{err:?}")),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn ssh_identity_rename(
+    app: AppHandle,
+    name: String,
+    new_name: String,
+) -> Result<SshIdentity, String> {
+    let new_name = validate_identity_name(&new_name)?;
+    let mut map = load_identities(&app)?;
+    if name.trim() != new_name && map.contains_key(&new_name) {
+        return Err(format!("SSH identity already exists: {new_name}"));
+    }
+    let entry = map
+        .remove(name.trim())
+        .ok_or_else(|| format!("SSH identity not found: {}", name.trim()))?;
+    map.insert(new_name.clone(), entry.clone());
+    save_identities(&app, &map)?;
+    Ok(to_identity(&app, new_name, entry))
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use russh::keys::{HashAlg, PublicKey};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::now_ms;
+
+pub(crate) const HOST_KEY_MISMATCH: &str = "HOST_KEY_MISMATCH";
+
+// Serializes read-modify-write cycles on the known hosts file; concurrent
+// connects to a fresh host would otherwise race on the first-use insert.
+static KNOWN_HOSTS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KnownHostEntry {
+    pub(crate) algorithm: String,
+    pub(crate) fingerprint: String,
+    pub(crate) public_key: String,
+    pub(crate) added_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KnownHost {
+    host_id: String,
+    #[serde(flatten)]
+    entry: KnownHostEntry,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HostKeyMismatch {
+    code: &'static str,
+    host: String,
+    port: u16,
+    expected_fingerprint: String,
+    actual_fingerprint: String,
+    actual_algorithm: String,
+    actual_public_key: String,
+}
+
+impl HostKeyMismatch {
+    /// Error string handed to the frontend: the code followed by JSON details,
+    /// so callers can `includes(HOST_KEY_MISMATCH)` like the VM password codes.
+    pub(crate) fn to_error(&self) -> String {
+        let details = serde_json::to_string(self).unwrap_or_default();
+        format!("{HOST_KEY_MISMATCH}:{details}")
+    }
+}
+
+pub(crate) fn host_id(host: &str, port: u16) -> String {
+    let host = host.trim().trim_start_matches('[').trim_end_matches(']');
+    let host = host.to_ascii_lowercase();
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+pub(crate) fn known_hosts_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("ssh");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir.join("known_hosts.json"))
+}
+
+fn load_known_hosts(path: &Path) -> Result<BTreeMap<String, KnownHostEntry>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    serde_json::from_str(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_known_hosts(path: &Path, map: &BTreeMap<String, KnownHostEntry>) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(map).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+fn entry_for_key(key: &PublicKey) -> Result<KnownHostEntry, String> {
+    Ok(KnownHostEntry {
+        algorithm: key.algorithm().as_str().to_string(),
+        fingerprint: key.fingerprint(HashAlg::Sha256).to_string(),
+        public_key: key.to_openssh().map_err(|err| format!("{err:?}"))?,
+        added_at: now_ms(),
+    })
+}
+
+/// Per-connection host key policy: trust on first use, reject changed keys.
+#[derive(Clone)]
+pub(crate) struct HostKeyVerifier {
+    path: PathBuf,
+    host: String,
+    port: u16,
+    rejection: Arc<Mutex<Option<HostKeyMismatch>>>,
+}
+
+impl HostKeyVerifier {
+    pub(crate) fn new(app: &AppHandle, host: &str, port: u16) -> Result<Self, String> {
+        Ok(Self::with_path(known_hosts_path(app)?, host, port))
+    }
+
+    pub(crate) fn with_path(path: PathBuf, host: &str, port: u16) -> Self {
+        Self {
+            path,
+            host: host.to_string(),
+            port,
+            rejection: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub(crate) fn check(&self, key: &PublicKey) -> Result<bool, String> {
+        let presented = entry_for_key(key)?;
+        let id = host_id(&self.host, self.port);
+
+        let _guard = KNOWN_HOSTS_LOCK.lock().expect("known hosts lock poisoned");
+        let mut map = load_known_hosts(&self.path)?;
+        match map.get(&id) {
+            Some(known) if known.fingerprint == presented.fingerprint => Ok(true),
+            Some(known) => {
+                let mismatch = HostKeyMismatch {
+                    code: HOST_KEY_MISMATCH,
+                    host: self.host.clone(),
+                    port: self.port,
+                    expected_fingerprint: known.fingerprint.clone(),
+                    actual_fingerprint: presented.fingerprint,
+                    actual_algorithm: presented.algorithm,
+                    actual_public_key: presented.public_key,
+                };
+                *self.rejection.lock().expect("host key rejection poisoned") = Some(mismatch);
+                Ok(false)
+            }
+            None => {
+                map.insert(id, presented);
+                save_known_hosts(&self.path, &map)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// The mismatch recorded by `check`, if the connection was refused because of it.
+    pub(crate) fn rejection(&self) -> Option<HostKeyMismatch> {
+        self.rejection
+            .lock()
+            .expect("host key rejection poisoned")
+            .clone()
+    }
+}
+
+#[tauri::command]
+pub(crate) fn host_key_list(app: AppHandle) -> Result<Vec<KnownHost>, String> {
+    let path = known_hosts_path(&app)?;
+    let _guard = KNOWN_HOSTS_LOCK.lock().expect("known hosts lock poisoned");
+    Ok(load_known_hosts(&path)?
+        .into_iter()
+        .map(|(host_id, entry)| KnownHost { host_id, entry })
+        .collect())
+}
+
+#[tauri::command]
+pub(crate) fn host_key_approve(
+    app: AppHandle,
+    host: String,
+    port: u16,
+    public_key: String,
+    fingerprint: String,
+) -> Result<KnownHost, String> {
+    let key = PublicKey::from_openssh(public_key.trim()).map_err(|err| format!("{err:?}"))?;
+    let entry = entry_for_key(&key)?;
+    // The fingerprint is what the user actually compared; refuse to pin a
+    // different key than the one they approved.
+    if entry.fingerprint != fingerprint.trim() {
+        return Err("Host key fingerprint does not match the approved fingerprint".to_string());
+    }
+
+    let path = known_hosts_path(&app)?;
+    let host_id = host_id(&host, port);
+    let _guard = KNOWN_HOSTS_LOCK.lock().expect("known hosts lock poisoned");
+    let mut map = load_known_hosts(&path)?;
+    map.insert(host_id.clone(), entry.clone());
+    save_known_hosts(&path, &map)?;
+    Ok(KnownHost { host_id, entry })
+}
+
+#[tauri::command]
+pub(crate) fn host_key_forget(app: AppHandle, host: String, port: u16) -> Result<(), String> {
+    let path = known_hosts_path(&app)?;
+    let _guard = KNOWN_HOSTS_LOCK.lock().expect("known hosts lock poisoned");
+    let mut map = load_known_hosts(&path)?;
+    if map.remove(&host_id(&host, port)).is_some() {
+        save_known_hosts(&path, &map)?;
+    }
+    Ok(())
+}
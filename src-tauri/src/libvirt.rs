@@ -0,0 +1,800 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::hypervisor::{self, check_name, check_uuid, GuestSnapshot, GuestVm, VmProvider};
+use crate::remote_host::{sh_quote, HostPlatform};
+use crate::{ssh_config, timeouts, RequestContext, SshConfig, TraceStore, VmStopMode};
+
+/// Checks for virsh and points it at the system instance unless the host
+/// picks another; the per-user session rarely has any VMs, but it is all a
+/// user outside the `libvirt` group can reach. No reachable daemon is
+/// `PROVIDER_UNAVAILABLE`.
+const PRELUDE: &str = r#"command -v virsh >/dev/null 2>&1 || { echo 'PROVIDER_NOT_INSTALLED: virsh not found' >&2; exit 127; }
+if [ -z "${LIBVIRT_DEFAULT_URI:-}" ]; then
+  LIBVIRT_DEFAULT_URI=qemu:///system
+  virsh -c "$LIBVIRT_DEFAULT_URI" uri >/dev/null 2>&1 || LIBVIRT_DEFAULT_URI=qemu:///session
+  export LIBVIRT_DEFAULT_URI
+fi
+err=$(virsh uri 2>&1 >/dev/null) || { echo "PROVIDER_UNAVAILABLE: cannot connect to $LIBVIRT_DEFAULT_URI: $err" >&2; exit 1; }"#;
+
+/// `body` runs with the domain UUID in `$u` when `vm_id` is given.
+fn script(platform: HostPlatform, vm_id: Option<&str>, body: &str) -> Result<String, String> {
+    if platform != HostPlatform::Posix {
+        return Err("libvirt requires a Linux host".to_string());
+    }
+    Ok(match vm_id {
+        Some(id) => format!("{PRELUDE}\nu={}\n{body}", sh_quote(id)),
+        None => format!("{PRELUDE}\n{body}"),
+    })
+}
+
+/// States in which the domain has a live QEMU process, as `vmrun list`
+/// counts a paused VM as running.
+fn state_is_running(state: &str) -> bool {
+    matches!(
+        state,
+        "running" | "idle" | "blocked" | "paused" | "in shutdown"
+    )
+}
+
+const LIST_SCRIPT: &str = r#"uuids=$(virsh list --all --uuid) || exit $?
+for u in $uuids; do
+  printf '%s\t%s\t%s\n' "$u" "$(virsh domname "$u")" "$(virsh domstate "$u")"
+done"#;
+
+/// Reads `uuid<TAB>name<TAB>state` lines from `LIST_SCRIPT`.
+fn parse_list_output(output: &str) -> Vec<GuestVm> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim_end_matches('\r').splitn(3, '\t');
+            let uuid = fields.next()?.trim();
+            let name = fields.next()?.trim();
+            let state = fields.next()?.trim();
+            (!uuid.is_empty()).then(|| GuestVm {
+                provider: VmProvider::Libvirt,
+                id: uuid.to_string(),
+                name: name.to_string(),
+                is_running: state_is_running(state),
+                state: state.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Every domain defined in libvirt, running or not.
+#[tauri::command]
+pub(crate) async fn libvirt_list_vms(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestVm>, String> {
+    hypervisor::list_command(&app, "libvirt_list_vms", &request_id, stream, |ctx| {
+        list_vms(&app, &store, &ssh, ctx, &request_id)
+    })
+    .await
+}
+
+pub(crate) async fn list_vms(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<Vec<GuestVm>, String> {
+    let out = hypervisor::run_script_with(
+        app,
+        store,
+        ssh,
+        ctx,
+        "libvirt_list_vms",
+        request_id,
+        true,
+        |platform| script(platform, None, LIST_SCRIPT),
+    )
+    .await?;
+    Ok(parse_list_output(&out))
+}
+
+#[tauri::command]
+pub(crate) async fn libvirt_start_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_start_vm",
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, Some(&vm_id), r#"virsh start "$u""#),
+    )
+    .await
+}
+
+/// Soft sends an ACPI shutdown; hard destroys (powers off) the domain.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn libvirt_stop_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    mode: Option<VmStopMode>,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    let cfg = ssh_config::resolve_alias(&app, &ssh)?;
+    let polls = timeouts::profile_for(&app, &cfg.host, cfg.port())?.stop_poll_budget;
+    let body = match mode.unwrap_or(VmStopMode::Soft) {
+        VmStopMode::Hard => r#"virsh destroy "$u""#.to_string(),
+        VmStopMode::Soft => format!(
+            r#"virsh shutdown "$u" || exit $?
+i=0
+while [ $i -lt {polls} ]; do
+  sleep 1
+  [ "$(virsh domstate "$u")" = "shut off" ] && {{ echo 'Domain shut off'; exit 0; }}
+  i=$((i+1))
+done
+echo 'Domain is still running after shutdown' >&2
+exit 1"#
+        ),
+    };
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_stop_vm",
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, Some(&vm_id), &body),
+    )
+    .await
+}
+
+/// One block per snapshot: a marker, `snapshot-info`'s `Key: value` lines
+/// and the creation time from its XML.
+const SNAPSHOT_LIST_SCRIPT: &str = r#"names=$(virsh snapshot-list --domain "$u" --name) || exit $?
+printf '%s\n' "$names" | while IFS= read -r n; do
+  [ -n "$n" ] || continue
+  echo '@@snapshot'
+  virsh snapshot-info --domain "$u" --snapshotname "$n"
+  printf 'Created: %s\n' "$(virsh snapshot-dumpxml --domain "$u" --snapshotname "$n" | sed -n 's:.*<creationTime>\([0-9]*\)</creationTime>.*:\1:p' | head -n 1)"
+done"#;
+
+fn parse_snapshot_list(output: &str) -> Vec<GuestSnapshot> {
+    output
+        .split("@@snapshot")
+        .filter_map(|block| {
+            let field = |key: &str| {
+                block.lines().find_map(|line| {
+                    let (k, v) = line.split_once(':')?;
+                    (k.trim() == key).then(|| v.trim().to_string())
+                })
+            };
+            let name = field("Name").filter(|name| !name.is_empty())?;
+            Some(GuestSnapshot {
+                id: name.clone(),
+                name,
+                parent_id: field("Parent").filter(|p| !p.is_empty() && p != "-"),
+                created_at: field("Created")
+                    .and_then(|secs| secs.parse::<u64>().ok())
+                    .map(|secs| secs.saturating_mul(1000)),
+                is_current: field("Current").as_deref() == Some("yes"),
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub(crate) async fn libvirt_snapshot_list(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestSnapshot>, String> {
+    check_uuid("VM id", &vm_id)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_snapshot_list",
+        request_id,
+        stream,
+        true,
+        |platform| script(platform, Some(&vm_id), SNAPSHOT_LIST_SCRIPT),
+    )
+    .await?;
+    Ok(parse_snapshot_list(&out))
+}
+
+/// `snapshot-create-as`, `snapshot-revert` or `snapshot-delete` for `name`.
+#[allow(clippy::too_many_arguments)]
+async fn snapshot_command(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    action: &str,
+    vm_id: &str,
+    name: &str,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", vm_id)?;
+    check_name("Snapshot name", name)?;
+    let name = sh_quote(name.trim());
+    let body = match action {
+        "libvirt_snapshot_create" => {
+            format!(r#"virsh snapshot-create-as --domain "$u" --name {name}"#)
+        }
+        "libvirt_snapshot_revert" => {
+            format!(r#"virsh snapshot-revert --domain "$u" --snapshotname {name}"#)
+        }
+        _ => format!(r#"virsh snapshot-delete --domain "$u" --snapshotname {name}"#),
+    };
+    hypervisor::run_script(
+        app,
+        store,
+        ssh,
+        action,
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, Some(vm_id), &body),
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn libvirt_snapshot_create(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_snapshot_create",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn libvirt_snapshot_revert(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_snapshot_revert",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn libvirt_snapshot_delete(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_snapshot_delete",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LibvirtDisk {
+    /// `disk`, `cdrom` or `floppy`.
+    device: String,
+    target: Option<String>,
+    /// Image file, block device or `pool/volume`.
+    source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LibvirtInterface {
+    /// `network`, `bridge`, `direct`, ...
+    kind: String,
+    mac: Option<String>,
+    /// Network or bridge name.
+    source: Option<String>,
+    model: Option<String>,
+}
+
+/// What the UI shows about a domain, from `virsh dumpxml`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LibvirtDomainInfo {
+    uuid: String,
+    name: String,
+    title: Option<String>,
+    description: Option<String>,
+    memory_kib: Option<u64>,
+    vcpus: Option<u32>,
+    /// `hvm` for full virtualization.
+    os_type: Option<String>,
+    arch: Option<String>,
+    machine: Option<String>,
+    disks: Vec<LibvirtDisk>,
+    interfaces: Vec<LibvirtInterface>,
+}
+
+/// Every `<tag ...>` in `xml` as (attributes, content); content is empty for
+/// self-closing tags. Enough for the flat, well-formed XML libvirt writes;
+/// elements of one name are never nested there.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        if !after.starts_with([' ', '>', '/', '\n', '\t', '\r']) {
+            rest = after;
+            continue;
+        }
+        let Some(end) = after.find('>') else { break };
+        let attrs = &after[..end];
+        let body = &after[end + 1..];
+        if let Some(attrs) = attrs.strip_suffix('/') {
+            found.push((attrs, ""));
+            rest = body;
+        } else {
+            let content_end = body.find(&close).unwrap_or(body.len());
+            found.push((attrs, &body[..content_end]));
+            rest = &body[content_end..];
+        }
+    }
+    found
+}
+
+fn element<'a>(xml: &'a str, tag: &str) -> Option<(&'a str, &'a str)> {
+    elements(xml, tag).into_iter().next()
+}
+
+/// Like `element`, but only a direct child of the root element counts, so
+/// the domain's `<memory>` is not confused with the one in `<numatune>`.
+fn child<'a>(xml: &'a str, tag: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0usize;
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let end = after.find('>')?;
+        let inner = &after[..end];
+        if inner.starts_with('/') {
+            depth = depth.saturating_sub(1);
+        } else if !inner.starts_with(['?', '!']) {
+            let name = inner
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            if depth == 1 && name == tag {
+                return element(&rest[start..], tag);
+            }
+            if !inner.ends_with('/') {
+                depth += 1;
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    None
+}
+
+fn text(xml: &str, tag: &str) -> Option<String> {
+    child(xml, tag)
+        .map(|(_, content)| unescape(content.trim()))
+        .filter(|t| !t.is_empty())
+}
+
+/// The value of `name='...'` (or double-quoted) in a start tag's attributes.
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    while let Some(pos) = rest.find(name) {
+        let before_ok = rest[..pos].ends_with(char::is_whitespace) || pos == 0;
+        let after = &rest[pos + name.len()..];
+        if before_ok {
+            if let Some(value) = after.strip_prefix('=') {
+                let quote = value.chars().next()?;
+                if quote == '\'' || quote == '"' {
+                    let value = &value[1..];
+                    let end = value.find(quote)?;
+                    return Some(unescape(&value[..end]));
+                }
+            }
+        }
+        rest = after;
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Memory in KiB; libvirt writes KiB but accepts other units on input.
+fn memory_kib(attrs: &str, value: &str) -> Option<u64> {
+    let value = value.trim().parse::<u64>().ok()?;
+    let factor = match attr(attrs, "unit").as_deref() {
+        None | Some("KiB") | Some("k") | Some("K") => 1,
+        Some("b") | Some("bytes") => return Some(value / 1024),
+        Some("MiB") | Some("M") => 1024,
+        Some("GiB") | Some("G") => 1024 * 1024,
+        Some("TiB") | Some("T") => 1024 * 1024 * 1024,
+        Some(_) => return None,
+    };
+    Some(value.saturating_mul(factor))
+}
+
+fn parse_domain_xml(xml: &str) -> LibvirtDomainInfo {
+    let os_type = child(xml, "os").and_then(|(_, os)| element(os, "type"));
+    let disks = elements(xml, "disk")
+        .into_iter()
+        .map(|(attrs, inner)| LibvirtDisk {
+            device: attr(attrs, "device").unwrap_or_else(|| "disk".to_string()),
+            target: element(inner, "target").and_then(|(a, _)| attr(a, "dev")),
+            source: element(inner, "source").and_then(|(a, _)| {
+                attr(a, "file").or_else(|| attr(a, "dev")).or_else(|| {
+                    let pool = attr(a, "pool")?;
+                    let volume = attr(a, "volume")?;
+                    Some(format!("{pool}/{volume}"))
+                })
+            }),
+        })
+        .collect();
+    let interfaces = elements(xml, "interface")
+        .into_iter()
+        .map(|(attrs, inner)| LibvirtInterface {
+            kind: attr(attrs, "type").unwrap_or_default(),
+            mac: element(inner, "mac").and_then(|(a, _)| attr(a, "address")),
+            source: element(inner, "source").and_then(|(a, _)| {
+                attr(a, "network")
+                    .or_else(|| attr(a, "bridge"))
+                    .or_else(|| attr(a, "dev"))
+            }),
+            model: element(inner, "model").and_then(|(a, _)| attr(a, "type")),
+        })
+        .collect();
+
+    LibvirtDomainInfo {
+        uuid: text(xml, "uuid").unwrap_or_default(),
+        name: text(xml, "name").unwrap_or_default(),
+        title: text(xml, "title"),
+        description: text(xml, "description"),
+        memory_kib: child(xml, "memory").and_then(|(a, v)| memory_kib(a, v)),
+        vcpus: text(xml, "vcpu").and_then(|v| v.parse().ok()),
+        os_type: os_type.map(|(_, t)| t.trim().to_string()),
+        arch: os_type.and_then(|(a, _)| attr(a, "arch")),
+        machine: os_type.and_then(|(a, _)| attr(a, "machine")),
+        disks,
+        interfaces,
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn libvirt_vm_info(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<LibvirtDomainInfo, String> {
+    check_uuid("VM id", &vm_id)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "libvirt_vm_info",
+        request_id,
+        stream,
+        true,
+        |platform| script(platform, Some(&vm_id), r#"virsh dumpxml "$u""#),
+    )
+    .await?;
+    Ok(parse_domain_xml(&out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `virsh dumpxml` of a running domain on libvirt 9.0, trimmed to what
+    /// the parser reads plus the neighbours that could confuse it.
+    const DOMAIN_XML: &str = r#"<domain type='kvm' id='3'>
+  <name>web01</name>
+  <uuid>0f3c5f0e-9a77-4f0b-8c4e-2a1d6b7e9c10</uuid>
+  <title>Web &amp; API</title>
+  <description>nginx &lt;prod&gt;</description>
+  <metadata>
+    <libosinfo:libosinfo xmlns:libosinfo="http://libosinfo.org/xmlns/libvirt/domain/1.0">
+      <libosinfo:os id="http://debian.org/debian/12"/>
+    </libosinfo:libosinfo>
+  </metadata>
+  <memory unit='KiB'>4194304</memory>
+  <currentMemory unit='KiB'>4194304</currentMemory>
+  <vcpu placement='static'>2</vcpu>
+  <resource>
+    <partition>/machine</partition>
+  </resource>
+  <os>
+    <type arch='x86_64' machine='pc-q35-7.2'>hvm</type>
+    <boot dev='hd'/>
+  </os>
+  <devices>
+    <emulator>/usr/bin/qemu-system-x86_64</emulator>
+    <disk type='file' device='disk'>
+      <driver name='qemu' type='qcow2' discard='unmap'/>
+      <source file='/var/lib/libvirt/images/web01.qcow2' index='2'/>
+      <backingStore type='file' index='3'>
+        <format type='qcow2'/>
+        <source file='/var/lib/libvirt/images/debian-12-base.qcow2'/>
+      </backingStore>
+      <target dev='vda' bus='virtio'/>
+      <alias name='virtio-disk0'/>
+    </disk>
+    <disk type='block' device='disk'>
+      <driver name='qemu' type='raw' cache='none' io='native'/>
+      <source dev='/dev/vg0/web01-data' index='1'/>
+      <backingStore/>
+      <target dev='vdb' bus='virtio'/>
+    </disk>
+    <disk type='volume' device='disk'>
+      <driver name='qemu' type='qcow2'/>
+      <source pool='default' volume='web01-logs.qcow2'/>
+      <target dev='vdc' bus='virtio'/>
+    </disk>
+    <disk type='file' device='cdrom'>
+      <driver name='qemu' type='raw'/>
+      <target dev='sda' bus='sata'/>
+      <readonly/>
+      <alias name='sata0-0-0'/>
+    </disk>
+    <interface type='network'>
+      <mac address='52:54:00:6b:3c:58'/>
+      <source network='default' portid='8a3c0f5e-1b2d-4c3e-9f4a-5b6c7d8e9f00' bridge='virbr0'/>
+      <target dev='vnet2'/>
+      <model type='virtio'/>
+    </interface>
+    <interface type='bridge'>
+      <mac address='52:54:00:a1:b2:c3'/>
+      <source bridge='br0'/>
+      <target dev='vnet3'/>
+      <model type='e1000e'/>
+    </interface>
+  </devices>
+</domain>
+"#;
+
+    #[test]
+    fn parses_domain_xml() {
+        let info = parse_domain_xml(DOMAIN_XML);
+        assert_eq!(info.name, "web01");
+        assert_eq!(info.uuid, "0f3c5f0e-9a77-4f0b-8c4e-2a1d6b7e9c10");
+        assert_eq!(info.title.as_deref(), Some("Web & API"));
+        assert_eq!(info.description.as_deref(), Some("nginx <prod>"));
+        assert_eq!(info.memory_kib, Some(4194304));
+        assert_eq!(info.vcpus, Some(2));
+        assert_eq!(info.os_type.as_deref(), Some("hvm"));
+        assert_eq!(info.arch.as_deref(), Some("x86_64"));
+        assert_eq!(info.machine.as_deref(), Some("pc-q35-7.2"));
+    }
+
+    #[test]
+    fn parses_file_block_pool_and_empty_cdrom_disks() {
+        let disks: Vec<_> = parse_domain_xml(DOMAIN_XML)
+            .disks
+            .into_iter()
+            .map(|d| (d.device, d.target, d.source))
+            .collect();
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            disks,
+            [
+                (
+                    "disk".to_string(),
+                    some("vda"),
+                    some("/var/lib/libvirt/images/web01.qcow2")
+                ),
+                ("disk".to_string(), some("vdb"), some("/dev/vg0/web01-data")),
+                (
+                    "disk".to_string(),
+                    some("vdc"),
+                    some("default/web01-logs.qcow2")
+                ),
+                ("cdrom".to_string(), some("sda"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_network_and_bridge_interfaces() {
+        let nics: Vec<_> = parse_domain_xml(DOMAIN_XML)
+            .interfaces
+            .into_iter()
+            .map(|i| (i.kind, i.mac, i.source, i.model))
+            .collect();
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            nics,
+            [
+                (
+                    "network".to_string(),
+                    some("52:54:00:6b:3c:58"),
+                    some("default"),
+                    some("virtio")
+                ),
+                (
+                    "bridge".to_string(),
+                    some("52:54:00:a1:b2:c3"),
+                    some("br0"),
+                    some("e1000e")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_memory_units() {
+        let xml =
+            "<domain type='kvm'>\n  <name>db</name>\n  <memory unit='GiB'>8</memory>\n</domain>";
+        assert_eq!(parse_domain_xml(xml).memory_kib, Some(8 * 1024 * 1024));
+    }
+
+    #[test]
+    fn ignores_numatune_memory() {
+        // Hand-edited definitions can put <numatune> before <memory>.
+        let xml = r#"<domain type='kvm'>
+  <name>numa01</name>
+  <numatune>
+    <memory mode='strict' nodeset='0-1'/>
+    <memnode cellid='0' mode='strict' nodeset='0'/>
+  </numatune>
+  <memory unit='KiB'>16777216</memory>
+  <vcpu placement='static'>8</vcpu>
+</domain>"#;
+        let info = parse_domain_xml(xml);
+        assert_eq!(info.memory_kib, Some(16777216));
+        assert_eq!(info.vcpus, Some(8));
+
+        let no_domain_memory =
+            "<domain type='kvm'>\n  <numatune>\n    <memory mode='strict' nodeset='0'/>\n  </numatune>\n</domain>";
+        assert_eq!(parse_domain_xml(no_domain_memory).memory_kib, None);
+    }
+
+    #[test]
+    fn elements_skip_longer_tag_names() {
+        let xml = "<memory unit='KiB'>1024</memory><memoryBacking><hugepages/></memoryBacking>";
+        assert_eq!(elements(xml, "memory"), [(" unit='KiB'", "1024")]);
+        assert_eq!(elements("<readonly/><shareable />", "readonly"), [("", "")]);
+    }
+
+    #[test]
+    fn attr_matches_whole_names_and_both_quotes() {
+        let attrs = r#" type='file' device="cdrom" xdev='no' dev='sda'"#;
+        assert_eq!(attr(attrs, "device").as_deref(), Some("cdrom"));
+        assert_eq!(attr(attrs, "dev").as_deref(), Some("sda"));
+        assert_eq!(attr(attrs, "type").as_deref(), Some("file"));
+        assert_eq!(attr(attrs, "bus"), None);
+        assert_eq!(
+            attr(" path='/srv/a&amp;b.iso'", "path").as_deref(),
+            Some("/srv/a&b.iso")
+        );
+    }
+
+    #[test]
+    fn unescapes_ampersand_last() {
+        assert_eq!(
+            unescape("&lt;a&gt; &quot;b&quot; &apos;c&apos;"),
+            "<a> \"b\" 'c'"
+        );
+        assert_eq!(unescape("&amp;lt;"), "&lt;");
+    }
+
+    /// `SNAPSHOT_LIST_SCRIPT` output for a root snapshot and its child.
+    const SNAPSHOT_LIST: &str = "@@snapshot
+Name:           clean-install
+Domain:         web01
+Current:        no
+State:          shutoff
+Location:       internal
+Parent:         -
+Children:       1
+Descendants:    1
+Metadata:       yes
+
+Created: 1718000000
+@@snapshot
+Name:           before-upgrade
+Domain:         web01
+Current:        yes
+State:          running
+Location:       internal
+Parent:         clean-install
+Children:       0
+Descendants:    0
+Metadata:       yes
+
+Created: 1718600000
+";
+
+    #[test]
+    fn parses_snapshot_info() {
+        let snapshots = parse_snapshot_list(SNAPSHOT_LIST);
+        assert_eq!(snapshots.len(), 2);
+
+        assert_eq!(snapshots[0].id, "clean-install");
+        assert_eq!(snapshots[0].name, "clean-install");
+        assert_eq!(snapshots[0].parent_id, None);
+        assert_eq!(snapshots[0].created_at, Some(1718000000000));
+        assert!(!snapshots[0].is_current);
+
+        assert_eq!(snapshots[1].name, "before-upgrade");
+        assert_eq!(snapshots[1].parent_id.as_deref(), Some("clean-install"));
+        assert_eq!(snapshots[1].created_at, Some(1718600000000));
+        assert!(snapshots[1].is_current);
+    }
+
+    #[test]
+    fn snapshot_without_creation_time() {
+        let out =
+            "@@snapshot\nName:           s1\nParent:         -\nCurrent:        yes\nCreated: \n";
+        let snapshots = parse_snapshot_list(out);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].created_at, None);
+        assert!(parse_snapshot_list("").is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{SshConfig, SshSession};
+
+const DEFAULT_IDLE_TTL_SECS: u64 = 120;
+const REAP_INTERVAL: Duration = Duration::from_secs(15);
+
+struct PooledSession {
+    session: SshSession,
+    created: Instant,
+    last_used: Instant,
+}
+
+impl PooledSession {
+    fn in_use(&self) -> bool {
+        // Every `SshSession` handed out holds a clone of the handle.
+        Arc::strong_count(&self.session.session) > 1
+    }
+
+    fn is_closed(&self) -> bool {
+        self.session.session.is_closed()
+    }
+}
+
+type Slot = Arc<tokio::sync::Mutex<Option<PooledSession>>>;
+
+/// Live SSH connections keyed by `SshConfig`, shared by every command.
+///
+/// Each key owns one authenticated `client::Handle`; callers get channels
+/// multiplexed over it instead of paying a fresh handshake per command.
+pub(crate) struct SessionPool {
+    slots: Mutex<HashMap<SshConfig, Slot>>,
+    idle_ttl_secs: AtomicU64,
+}
+
+impl Default for SessionPool {
+    fn default() -> Self {
+        Self {
+            slots: Mutex::new(HashMap::new()),
+            idle_ttl_secs: AtomicU64::new(DEFAULT_IDLE_TTL_SECS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PooledSessionInfo {
+    host: String,
+    port: u16,
+    user: String,
+    in_use: bool,
+    age_ms: u64,
+    idle_ms: u64,
+}
+
+impl SessionPool {
+    fn slot(&self, cfg: &SshConfig) -> Slot {
+        let mut guard = self.slots.lock().expect("session pool poisoned");
+        guard.entry(cfg.clone()).or_default().clone()
+    }
+
+    fn slots(&self) -> Vec<(SshConfig, Slot)> {
+        let guard = self.slots.lock().expect("session pool poisoned");
+        guard
+            .iter()
+            .map(|(cfg, slot)| (cfg.clone(), slot.clone()))
+            .collect()
+    }
+
+    fn idle_ttl(&self) -> Duration {
+        Duration::from_secs(self.idle_ttl_secs.load(Ordering::Relaxed))
+    }
+
+    /// Returns a session for `cfg`, reusing the pooled connection when it is
+    /// still alive and dialing a new one otherwise.
+    pub(crate) async fn acquire(
+        &self,
+        app: &AppHandle,
+        cfg: &SshConfig,
+    ) -> Result<SshSession, String> {
+        let slot = self.slot(cfg);
+        // Holding the slot lock across the connect keeps concurrent callers
+        // for the same host from racing to open duplicate connections.
+        let mut guard = slot.lock().await;
+
+        if let Some(pooled) = guard.as_mut() {
+            if !pooled.is_closed() {
+                pooled.last_used = Instant::now();
+                return Ok(pooled.session.clone());
+            }
+        }
+        *guard = None;
+
+        let session = crate::ssh_open(app, cfg).await?;
+        let now = Instant::now();
+        *guard = Some(PooledSession {
+            session: session.clone(),
+            created: now,
+            last_used: now,
+        });
+        Ok(session)
+    }
+
+    /// Drops and disconnects the pooled connection for `cfg`.
+    pub(crate) async fn evict(&self, cfg: &SshConfig) {
+        let slot = self.slot(cfg);
+        let pooled = slot.lock().await.take();
+        if let Some(pooled) = pooled {
+            let _ = pooled.session.close().await;
+        }
+    }
+
+    /// Closes sessions that are dead or have sat unused longer than the TTL.
+    async fn reap(&self) {
+        let ttl = self.idle_ttl();
+        for (_, slot) in self.slots() {
+            let Ok(mut guard) = slot.try_lock() else {
+                // Someone is connecting or acquiring right now.
+                continue;
+            };
+            let expired = match guard.as_ref() {
+                Some(pooled) if pooled.is_closed() => true,
+                Some(pooled) if pooled.in_use() => false,
+                Some(pooled) => pooled.last_used.elapsed() >= ttl,
+                None => false,
+            };
+            if expired {
+                if let Some(pooled) = guard.take() {
+                    let _ = pooled.session.close().await;
+                }
+            }
+        }
+
+        let mut guard = self.slots.lock().expect("session pool poisoned");
+        guard.retain(|_, slot| {
+            slot.try_lock()
+                .map(|pooled| pooled.is_some())
+                .unwrap_or(true)
+        });
+    }
+
+    async fn close_all(&self) {
+        for (cfg, _) in self.slots() {
+            self.evict(&cfg).await;
+        }
+        self.slots.lock().expect("session pool poisoned").clear();
+    }
+
+    async fn list(&self) -> Vec<PooledSessionInfo> {
+        let mut out = Vec::new();
+        for (cfg, slot) in self.slots() {
+            let guard = slot.lock().await;
+            if let Some(pooled) = guard.as_ref() {
+                if pooled.is_closed() {
+                    continue;
+                }
+                out.push(PooledSessionInfo {
+                    host: cfg.host.clone(),
+                    port: cfg.port(),
+                    user: cfg.user.clone(),
+                    in_use: pooled.in_use(),
+                    age_ms: pooled.created.elapsed().as_millis() as u64,
+                    idle_ms: pooled.last_used.elapsed().as_millis() as u64,
+                });
+            }
+        }
+        out
+    }
+}
+
+/// Background task that periodically closes idle pooled sessions.
+pub(crate) fn spawn_reaper(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(REAP_INTERVAL).await;
+            app.state::<SessionPool>().reap().await;
+        }
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn ssh_pool_list(
+    pool: tauri::State<'_, SessionPool>,
+) -> Result<Vec<PooledSessionInfo>, String> {
+    Ok(pool.list().await)
+}
+
+#[tauri::command]
+pub(crate) fn ssh_pool_set_idle_ttl(
+    pool: tauri::State<'_, SessionPool>,
+    idle_ttl_secs: u64,
+) -> Result<(), String> {
+    if idle_ttl_secs == 0 || idle_ttl_secs > 24 * 60 * 60 {
+        return Err("Idle TTL must be between 1 second and 24 hours".to_string());
+    }
+    pool.idle_ttl_secs.store(idle_ttl_secs, Ordering::Relaxed);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn ssh_pool_clear(pool: tauri::State<'_, SessionPool>) -> Result<(), String> {
+    pool.close_all().await;
+    Ok(())
+}
@@ -0,0 +1,688 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::hypervisor::{self, GuestSnapshot, GuestVm, VmProvider};
+use crate::remote_host::HostPlatform;
+use crate::{ssh_config, timeouts, RequestContext, SshConfig, TraceStore, VmStopMode};
+
+/// Prefix on errors from a non-root user whose sudo asks for a password to
+/// run qm; scripts print it verbatim.
+pub(crate) const SUDO_PASSWORD_REQUIRED: &str = "SUDO_PASSWORD_REQUIRED";
+
+/// Defines `qm` for the script body. qm lives in /usr/sbin, which is not on
+/// a normal user's PATH, and only runs as root, so other users go through
+/// `sudo -n`: it fails instead of waiting for a password nobody can type.
+/// UTC makes `listsnapshot` times unambiguous.
+const PRELUDE: &str = r#"qm_bin=$(command -v qm 2>/dev/null || { [ -x /usr/sbin/qm ] && echo /usr/sbin/qm; })
+if [ -z "$qm_bin" ]; then echo 'PROVIDER_NOT_INSTALLED: qm not found' >&2; exit 127; fi
+if [ "$(id -u)" -eq 0 ]; then
+  qm() { TZ=UTC "$qm_bin" "$@"; }
+else
+  err=$(sudo -n env true 2>&1) || case "$err" in
+    *"password is required"*) echo 'SUDO_PASSWORD_REQUIRED: sudo asks for a password to run qm; connect as root or allow it with NOPASSWD' >&2; exit 1 ;;
+  esac
+  qm() { sudo -n env TZ=UTC "$qm_bin" "$@"; }
+fi"#;
+
+fn script(platform: HostPlatform, body: &str) -> Result<String, String> {
+    match platform {
+        HostPlatform::Posix => Ok(format!("{PRELUDE}\n{body}")),
+        HostPlatform::Windows => Err("Proxmox VE requires a Linux host".to_string()),
+    }
+}
+
+/// Proxmox VM ids are positive integers; checked before they reach a script.
+fn check_vmid(id: &str) -> Result<(), String> {
+    if !id.is_empty() && id.len() <= 9 && id.bytes().all(|b| b.is_ascii_digit()) && id != "0" {
+        Ok(())
+    } else {
+        Err(format!("VM id is not a Proxmox VMID: {id}"))
+    }
+}
+
+/// Proxmox's own rule for snapshot names, which also keeps them safe to
+/// pass unquoted.
+fn check_snapshot_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = (2..=40).contains(&name.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Snapshot name must start with a letter and contain 2-40 letters, digits, '-' or '_': {name}"
+        ))
+    }
+}
+
+/// One row of `qm list`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxmoxVm {
+    pub(crate) vmid: u32,
+    pub(crate) name: String,
+    /// `running`, `stopped` or `paused`... as qm prints it.
+    pub(crate) status: String,
+    pub(crate) mem_mb: u64,
+    pub(crate) bootdisk_gb: f64,
+    /// `None` when the VM has no QEMU process (qm prints 0).
+    pub(crate) pid: Option<u32>,
+}
+
+impl From<ProxmoxVm> for GuestVm {
+    fn from(vm: ProxmoxVm) -> Self {
+        GuestVm {
+            provider: VmProvider::Proxmox,
+            id: vm.vmid.to_string(),
+            name: vm.name,
+            is_running: vm.status == "running",
+            state: vm.status,
+        }
+    }
+}
+
+/// Reads `qm list`: a header, then whitespace-separated columns. Names are
+/// DNS names, but qm shows a VM without one as `VM <vmid>`, so the name is
+/// whatever lies between the VMID and the last four columns.
+fn parse_list_output(output: &str) -> Result<Vec<ProxmoxVm>, String> {
+    let mut vms = Vec::new();
+    for line in output.lines() {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        if fields.is_empty() || fields[0] == "VMID" {
+            continue;
+        }
+        let [vmid, ref name @ .., status, mem, disk, pid] = fields[..] else {
+            return Err(format!("Unexpected qm list line: {}", line.trim()));
+        };
+        if name.is_empty() {
+            return Err(format!("Unexpected qm list line: {}", line.trim()));
+        }
+        let number_err = |err: std::num::ParseIntError| format!("{err} in qm list line: {line}");
+        vms.push(ProxmoxVm {
+            vmid: vmid.parse().map_err(number_err)?,
+            name: name.join(" "),
+            status: status.to_string(),
+            mem_mb: mem.parse().map_err(number_err)?,
+            bootdisk_gb: disk
+                .parse()
+                .map_err(|err| format!("{err} in qm list line: {line}"))?,
+            pid: Some(pid.parse().map_err(number_err)?).filter(|pid| *pid != 0),
+        });
+    }
+    Ok(vms)
+}
+
+/// VMs on the node the SSH session lands on; other cluster nodes' VMs are
+/// listed by connecting to those nodes.
+#[tauri::command]
+pub(crate) async fn proxmox_list_vms(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<ProxmoxVm>, String> {
+    hypervisor::list_command(&app, "proxmox_list_vms", &request_id, stream, |ctx| {
+        list_vms(&app, &store, &ssh, ctx, &request_id)
+    })
+    .await
+}
+
+pub(crate) async fn list_vms(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<Vec<ProxmoxVm>, String> {
+    let out = hypervisor::run_script_with(
+        app,
+        store,
+        ssh,
+        ctx,
+        "proxmox_list_vms",
+        request_id,
+        true,
+        |platform| script(platform, "qm list"),
+    )
+    .await?;
+    parse_list_output(&out)
+}
+
+/// `qm status --verbose`, top-level keys only.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxmoxVmStatus {
+    pub(crate) vmid: Option<u32>,
+    pub(crate) name: Option<String>,
+    /// `running` or `stopped`.
+    pub(crate) status: String,
+    /// QEMU's own view, e.g. `paused` while `status` is still `running`.
+    pub(crate) qmpstatus: Option<String>,
+    pub(crate) uptime_secs: Option<u64>,
+    pub(crate) cpus: Option<f64>,
+    pub(crate) mem_bytes: Option<u64>,
+    pub(crate) maxmem_bytes: Option<u64>,
+    pub(crate) pid: Option<u32>,
+    /// Set while a backup, migration, snapshot... holds the VM.
+    pub(crate) lock: Option<String>,
+}
+
+/// `key: value` lines; indented lines belong to nested blocks (`blockstat`,
+/// `nics`, ...) and are skipped.
+fn parse_status_output(output: &str) -> Result<ProxmoxVmStatus, String> {
+    let mut status = ProxmoxVmStatus::default();
+    let mut seen_status = false;
+    for line in output.lines() {
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "status" => {
+                status.status = value.to_string();
+                seen_status = true;
+            }
+            "vmid" => status.vmid = value.parse().ok(),
+            "name" => status.name = Some(value.to_string()),
+            "qmpstatus" => status.qmpstatus = Some(value.to_string()),
+            "uptime" => status.uptime_secs = value.parse().ok(),
+            "cpus" => status.cpus = value.parse().ok(),
+            "mem" => status.mem_bytes = value.parse().ok(),
+            "maxmem" => status.maxmem_bytes = value.parse().ok(),
+            "pid" => status.pid = value.parse().ok(),
+            "lock" => status.lock = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if seen_status {
+        Ok(status)
+    } else {
+        Err(format!(
+            "qm status printed no status: {}",
+            output.lines().next().unwrap_or("").trim()
+        ))
+    }
+}
+
+#[tauri::command]
+pub(crate) async fn proxmox_vm_status(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<ProxmoxVmStatus, String> {
+    check_vmid(&vm_id)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_vm_status",
+        request_id,
+        stream,
+        true,
+        |platform| script(platform, &format!("qm status {vm_id} --verbose")),
+    )
+    .await?;
+    parse_status_output(&out)
+}
+
+#[tauri::command]
+pub(crate) async fn proxmox_start_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_vmid(&vm_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_start_vm",
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, &format!("qm start {vm_id}")),
+    )
+    .await
+}
+
+/// Soft sends an ACPI shutdown; hard stops the QEMU process.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn proxmox_stop_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    mode: Option<VmStopMode>,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_vmid(&vm_id)?;
+    let cfg = ssh_config::resolve_alias(&app, &ssh)?;
+    let polls = timeouts::profile_for(&app, &cfg.host, cfg.port())?.stop_poll_budget;
+    let body = match mode.unwrap_or(VmStopMode::Soft) {
+        VmStopMode::Soft => format!("qm shutdown {vm_id} --timeout {polls}"),
+        VmStopMode::Hard => format!("qm stop {vm_id}"),
+    };
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_stop_vm",
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, &body),
+    )
+    .await
+}
+
+/// `yyyy-mm-dd hh:mm:ss` in UTC as Unix milliseconds.
+fn parse_utc_timestamp(text: &str) -> Option<u64> {
+    let (date, time) = text.split_once(' ')?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Days from civil date (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    u64::try_from(secs).ok().map(|secs| secs * 1000)
+}
+
+/// Reads `qm listsnapshot`, a tree drawn with `` `-> `` markers whose
+/// indentation gives the parent. The `current` entry marks where the VM
+/// runs from and is not a snapshot itself.
+fn parse_snapshot_output(output: &str) -> Vec<GuestSnapshot> {
+    let mut snapshots: Vec<GuestSnapshot> = Vec::new();
+    // (column of the marker, name) of the path from the root to the last row.
+    let mut path: Vec<(usize, String)> = Vec::new();
+    for line in output.lines() {
+        let Some(column) = line.find("`->") else {
+            continue;
+        };
+        let rest = line[column + 3..].trim();
+        let Some(name) = rest.split_whitespace().next() else {
+            continue;
+        };
+        while path.last().is_some_and(|(c, _)| *c >= column) {
+            path.pop();
+        }
+        let parent_id = path.last().map(|(_, parent)| parent.clone());
+        if name == "current" {
+            if let Some(parent) = &parent_id {
+                for snapshot in snapshots.iter_mut() {
+                    snapshot.is_current = snapshot.id == *parent;
+                }
+            }
+            continue;
+        }
+        let created_at = rest[name.len()..]
+            .trim_start()
+            .get(..19)
+            .and_then(parse_utc_timestamp);
+        snapshots.push(GuestSnapshot {
+            id: name.to_string(),
+            name: name.to_string(),
+            parent_id,
+            created_at,
+            is_current: false,
+        });
+        path.push((column, name.to_string()));
+    }
+    snapshots
+}
+
+#[tauri::command]
+pub(crate) async fn proxmox_snapshot_list(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestSnapshot>, String> {
+    check_vmid(&vm_id)?;
+    let out = hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_snapshot_list",
+        request_id,
+        stream,
+        true,
+        |platform| script(platform, &format!("qm listsnapshot {vm_id}")),
+    )
+    .await?;
+    Ok(parse_snapshot_output(&out))
+}
+
+/// `qm snapshot`, `qm rollback` or `qm delsnapshot` for `name`.
+#[allow(clippy::too_many_arguments)]
+async fn snapshot_command(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    action: &str,
+    subcommand: &str,
+    vm_id: &str,
+    name: &str,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_vmid(vm_id)?;
+    check_snapshot_name(name)?;
+    hypervisor::run_script(
+        app,
+        store,
+        ssh,
+        action,
+        request_id,
+        stream,
+        false,
+        |platform| script(platform, &format!("qm {subcommand} {vm_id} {name}")),
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn proxmox_snapshot_create(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_snapshot_create",
+        "snapshot",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+/// Stops the VM if it runs; it comes back running only for snapshots that
+/// include RAM.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn proxmox_snapshot_rollback(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_snapshot_rollback",
+        "rollback",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn proxmox_snapshot_delete(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    name: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    snapshot_command(
+        &app,
+        &store,
+        &ssh,
+        "proxmox_snapshot_delete",
+        "delsnapshot",
+        &vm_id,
+        &name,
+        request_id,
+        stream,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `qm list` on a PVE 8.2 node, trailing spaces included; 102 was
+    /// created without a name.
+    const QM_LIST: &str = "      VMID NAME                 STATUS     MEM(MB)    BOOTDISK(GB) PID       \n       100 debian12-build       running    4096              32.00 21876     \n       101 win11-test           stopped    8192              64.00 0         \n       102 VM 102               stopped    512                0.00 0         \n      9000 ubuntu-template      stopped    2048               3.50 0         \n";
+
+    /// `qm status 100 --verbose`, nested blocks shortened.
+    const QM_STATUS_RUNNING: &str = "balloon: 4294967296
+ballooninfo:
+\tactual: 4294967296
+\tmax_mem: 4294967296
+blockstat:
+\tscsi0:
+\t\trd_bytes: 1249312768
+\t\twr_bytes: 204800
+cpus: 4
+disk: 0
+diskread: 1249312768
+maxdisk: 34359738368
+maxmem: 4294967296
+mem: 1852612608
+name: debian12-build
+netin: 5831522
+nics:
+\ttap100i0:
+\t\tnetin: 5831522
+pid: 21876
+proxmox-support:
+qmpstatus: running
+running-machine: pc-i440fx-8.1+pve0
+running-qemu: 8.1.5
+status: running
+uptime: 86731
+vmid: 100
+";
+
+    /// `qm listsnapshot 100` with a branch: `fix` and `before-upgrade` both
+    /// derive from `base`, and the VM runs from `after-upgrade`.
+    const QM_LISTSNAPSHOT: &str =
+        "`-> base                        2024-03-01 09:15:00     clean install
+ `-> before-upgrade             2024-04-10 17:02:11     no-description
+  `-> after-upgrade             2024-04-11 08:00:59     kernel 6.8
+   `-> current                                          You are here!
+ `-> fix                        2024-03-02 10:00:00     no-description
+";
+
+    #[test]
+    fn parses_qm_list() {
+        let vms = parse_list_output(QM_LIST).unwrap();
+        assert_eq!(vms.len(), 4);
+        assert_eq!(
+            vms[0],
+            ProxmoxVm {
+                vmid: 100,
+                name: "debian12-build".to_string(),
+                status: "running".to_string(),
+                mem_mb: 4096,
+                bootdisk_gb: 32.0,
+                pid: Some(21876),
+            }
+        );
+        assert_eq!(vms[1].status, "stopped");
+        assert_eq!(vms[1].pid, None);
+        assert_eq!(vms[2].vmid, 102);
+        assert_eq!(vms[2].name, "VM 102");
+        assert_eq!(vms[2].mem_mb, 512);
+        assert_eq!(vms[2].pid, None);
+        assert_eq!(vms[3].vmid, 9000);
+        assert_eq!(vms[3].bootdisk_gb, 3.5);
+    }
+
+    #[test]
+    fn qm_list_maps_to_guest_vms() {
+        let vms = parse_list_output(QM_LIST)
+            .unwrap()
+            .into_iter()
+            .map(GuestVm::from)
+            .collect::<Vec<_>>();
+        assert_eq!(vms[0].provider, VmProvider::Proxmox);
+        assert_eq!(vms[0].id, "100");
+        assert!(vms[0].is_running);
+        assert!(!vms[1].is_running);
+    }
+
+    #[test]
+    fn empty_qm_list_is_no_vms() {
+        assert!(parse_list_output("").unwrap().is_empty());
+        let header_only =
+            "      VMID NAME                 STATUS     MEM(MB)    BOOTDISK(GB) PID       \n";
+        assert!(parse_list_output(header_only).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_qm_list_line_is_an_error() {
+        let err = parse_list_output("       100 debian12 running 4096\n").unwrap_err();
+        assert!(err.contains("Unexpected qm list line"), "{err}");
+        let err = parse_list_output("       100 running 4096 32.00 0\n").unwrap_err();
+        assert!(err.contains("Unexpected qm list line"), "{err}");
+        let err = parse_list_output("       abc vm running 4096 32.00 0\n").unwrap_err();
+        assert!(err.contains("qm list line"), "{err}");
+    }
+
+    #[test]
+    fn parses_verbose_qm_status() {
+        let status = parse_status_output(QM_STATUS_RUNNING).unwrap();
+        assert_eq!(
+            status,
+            ProxmoxVmStatus {
+                vmid: Some(100),
+                name: Some("debian12-build".to_string()),
+                status: "running".to_string(),
+                qmpstatus: Some("running".to_string()),
+                uptime_secs: Some(86731),
+                cpus: Some(4.0),
+                mem_bytes: Some(1852612608),
+                maxmem_bytes: Some(4294967296),
+                pid: Some(21876),
+                lock: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_stopped_and_locked_qm_status() {
+        let status = parse_status_output("status: stopped\n").unwrap();
+        assert_eq!(status.status, "stopped");
+        assert_eq!(status.pid, None);
+
+        let status = parse_status_output(
+            "lock: backup\nname: win11-test\nqmpstatus: paused\nstatus: running\nvmid: 101\n",
+        )
+        .unwrap();
+        assert_eq!(status.lock.as_deref(), Some("backup"));
+        assert_eq!(status.qmpstatus.as_deref(), Some("paused"));
+    }
+
+    #[test]
+    fn qm_status_without_status_is_an_error() {
+        let err = parse_status_output(
+            "Configuration file 'nodes/pve1/qemu-server/123.conf' does not exist\n",
+        )
+        .unwrap_err();
+        assert!(err.contains("does not exist"), "{err}");
+    }
+
+    #[test]
+    fn parses_snapshot_tree() {
+        let snapshots = parse_snapshot_output(QM_LISTSNAPSHOT);
+        let summary = snapshots
+            .iter()
+            .map(|s| (s.name.as_str(), s.parent_id.as_deref(), s.is_current))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("base", None, false),
+                ("before-upgrade", Some("base"), false),
+                ("after-upgrade", Some("before-upgrade"), true),
+                ("fix", Some("base"), false),
+            ]
+        );
+        assert_eq!(snapshots[0].id, "base");
+        // 2024-03-01 09:15:00 UTC
+        assert_eq!(snapshots[0].created_at, Some(1_709_284_500_000));
+    }
+
+    #[test]
+    fn snapshot_list_without_snapshots() {
+        let snapshots = parse_snapshot_output(
+            "`-> current                                          You are here!\n",
+        );
+        assert!(snapshots.is_empty());
+    }
+
+    #[test]
+    fn parses_utc_timestamps() {
+        assert_eq!(parse_utc_timestamp("1970-01-01 00:00:00"), Some(0));
+        assert_eq!(
+            parse_utc_timestamp("2000-02-29 23:59:59"),
+            Some(951_868_799_000)
+        );
+        assert_eq!(parse_utc_timestamp("You are here!"), None);
+        assert_eq!(parse_utc_timestamp("2024-13-01 00:00:00"), None);
+    }
+
+    #[test]
+    fn validates_ids_and_names() {
+        assert!(check_vmid("100").is_ok());
+        assert!(check_vmid("0").is_err());
+        assert!(check_vmid("100; reboot").is_err());
+        assert!(check_vmid("").is_err());
+
+        assert!(check_snapshot_name("before-upgrade_2").is_ok());
+        assert!(check_snapshot_name("1st").is_err());
+        assert!(check_snapshot_name("a").is_err());
+        assert!(check_snapshot_name("has space").is_err());
+        assert!(check_snapshot_name("x'; rm -rf /").is_err());
+    }
+}
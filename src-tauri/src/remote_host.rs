@@ -0,0 +1,799 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::known_hosts::host_id;
+use crate::vmrun::{self, DetectedVmrun, VmrunTarget, VmwareHostType};
+use crate::{
+    normalize_vmx_key, parse_json_string_array, powershell_encoded, powershell_prelude,
+    ps_single_quote_escape, retry, ssh_connect, RequestContext, SshConfig, SshSession, VmStopMode,
+};
+
+/// Operating system family of a VMware host, which decides the shell its
+/// scripts are written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum HostPlatform {
+    Windows,
+    /// Linux (or another Unix) with a POSIX `sh`.
+    Posix,
+}
+
+impl HostPlatform {
+    pub(crate) fn host(self, vmrun: VmrunTarget) -> Box<dyn RemoteHost> {
+        match self {
+            HostPlatform::Windows => Box::new(WindowsHost { vmrun }),
+            HostPlatform::Posix => Box::new(PosixHost { vmrun }),
+        }
+    }
+
+    /// Command line that runs `script` through this platform's shell.
+    pub(crate) fn encode(self, script: &str) -> String {
+        self.host(VmrunTarget::default()).encode(script)
+    }
+}
+
+/// Everything VM commands need to know about the host's shell: how to send a
+/// script, where vmrun lives, how to find and kill processes and how paths
+/// compare. Scripts are returned unencoded so traces show them readably.
+pub(crate) trait RemoteHost: Send + Sync {
+    /// Command line that runs `script` through the host's shell.
+    fn encode(&self, script: &str) -> String;
+
+    /// Prints the vmrun it finds in the registry, on PATH or in a known
+    /// install dir, then the host type it belongs to. Needs no `VmrunTarget`.
+    fn vmrun_detect_script(&self) -> String;
+
+    /// Script lines that check the configured vmrun and leave its path in
+    /// `$vmrun` and its connection arguments ready to pass.
+    fn vmrun_locator(&self) -> String;
+
+    /// Prints vmrun's path, then its version.
+    fn vmrun_probe_script(&self) -> String;
+
+    /// Runs `vmrun list`; output is for `parse_vmrun_list_output`.
+    fn list_running_script(&self) -> String;
+
+    fn start_script(&self, vmx_path: &str, password: Option<&str>, start_polls: u32) -> String;
+
+    /// One `vmrun stop` attempt; prints `STOP <label> exit=<code>` first.
+    fn stop_script(
+        &self,
+        vmx_path: &str,
+        mode: &VmStopMode,
+        password: Option<&str>,
+        label: &str,
+    ) -> String;
+
+    /// A stop run outside the SSH session, for hosts where vmrun cannot reach
+    /// VMs started in another session. `None` when the host has no such need.
+    fn task_stop_script(
+        &self,
+        vmx_path: &str,
+        mode: &VmStopMode,
+        password: Option<&str>,
+    ) -> Option<String>;
+
+    /// Force-kills the `vmware-vmx` process running `vmx_path`.
+    fn kill_vm_process_script(&self, vmx_path: &str) -> String;
+
+    /// Kills every process started by `command` (as built by `encode`).
+    /// `None` when nothing identifies them.
+    fn kill_process_tree_script(&self, command: &str) -> Option<String>;
+
+    /// Prints the SHA-256 of the file SFTP calls `path`.
+    fn sha256_script(&self, path: &str) -> String;
+
+    /// Lists `.vmx` files under `roots`, or the default VM folders when `None`.
+    fn scan_script(&self, roots: Option<&[String]>) -> Result<String, String>;
+
+    fn parse_paths(&self, output: &str) -> Result<Vec<String>, String>;
+
+    /// Comparable form of a VMX path as this host's file system sees it.
+    fn normalize_path(&self, path: &str) -> String;
+
+    fn same_path(&self, a: &str, b: &str) -> bool {
+        self.normalize_path(a) == self.normalize_path(b)
+    }
+
+    /// `script` as it may appear in traces: without the `-p` password.
+    fn redact(&self, script: &str) -> String;
+}
+
+/// What was detected about each host, for settings that leave it unset.
+#[derive(Default)]
+pub(crate) struct HostCache {
+    platforms: Mutex<HashMap<String, HostPlatform>>,
+    vmruns: Mutex<HashMap<String, DetectedVmrun>>,
+}
+
+impl HostCache {
+    pub(crate) fn forget_vmrun(&self, key: &str) {
+        self.vmruns.lock().expect("host cache poisoned").remove(key);
+    }
+}
+
+/// The host implementation for `ssh`: its configured platform and vmrun,
+/// else the ones detected on first use.
+pub(crate) async fn host_for(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+) -> Result<Box<dyn RemoteHost>, String> {
+    let platform = platform_for(app, ssh, ctx).await?;
+    let settings = vmrun::settings_for(app, &ssh.host, ssh.port())?;
+    let detected = match settings.vmrun_path {
+        Some(_) => None,
+        None => Some(detected_vmrun(app, ssh, ctx).await?),
+    };
+    Ok(platform.host(settings.target(detected.as_ref())))
+}
+
+pub(crate) async fn platform_for(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+) -> Result<HostPlatform, String> {
+    if let Some(platform) = known_platform(app, ssh) {
+        return Ok(platform);
+    }
+
+    let key = host_id(&ssh.host, ssh.port());
+    let cache = app.state::<HostCache>();
+    let policy = retry::policy_for(app, ssh)?;
+    let (res, _) = retry::with_retry(policy, || detect_platform(app, ssh, ctx)).await;
+    let platform = res?;
+    cache
+        .platforms
+        .lock()
+        .expect("host cache poisoned")
+        .insert(key, platform);
+    Ok(platform)
+}
+
+/// The vmrun detected on `ssh`'s host, found once and then cached.
+pub(crate) async fn detected_vmrun(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+) -> Result<DetectedVmrun, String> {
+    let key = host_id(&ssh.host, ssh.port());
+    let cache = app.state::<HostCache>();
+    if let Some(detected) = cache.vmruns.lock().expect("host cache poisoned").get(&key) {
+        return Ok(detected.clone());
+    }
+
+    let host = platform_for(app, ssh, ctx)
+        .await?
+        .host(VmrunTarget::default());
+    let policy = retry::policy_for(app, ssh)?;
+    let (res, _) = retry::with_retry(policy, || detect_vmrun(app, ssh, ctx, host.as_ref())).await;
+    let detected = res?;
+    remember_vmrun(app, &key, &detected);
+    Ok(detected)
+}
+
+pub(crate) fn remember_vmrun(app: &AppHandle, key: &str, detected: &DetectedVmrun) {
+    app.state::<HostCache>()
+        .vmruns
+        .lock()
+        .expect("host cache poisoned")
+        .insert(key.to_string(), detected.clone());
+}
+
+async fn detect_vmrun(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+    host: &dyn RemoteHost,
+) -> Result<DetectedVmrun, String> {
+    let session = ssh_connect(app, ssh).await?.with_request(ctx.clone());
+    let out = session
+        .exec_collect(&host.encode(&host.vmrun_detect_script()))
+        .await?;
+    parse_detected_vmrun(&out)
+}
+
+/// Reads the path and host type printed by `vmrun_detect_script`.
+pub(crate) fn parse_detected_vmrun(output: &str) -> Result<DetectedVmrun, String> {
+    let mut lines = output.lines().map(str::trim).filter(|l| !l.is_empty());
+    let path = lines
+        .next()
+        .ok_or_else(|| "vmrun detection printed nothing".to_string())?;
+    let host_type = match lines.next() {
+        Some("player") => VmwareHostType::Player,
+        _ => VmwareHostType::Ws,
+    };
+    Ok(DetectedVmrun {
+        path: path.to_string(),
+        host_type,
+    })
+}
+
+/// The platform configured for `ssh` or cached from an earlier detection.
+pub(crate) fn known_platform(app: &AppHandle, ssh: &SshConfig) -> Option<HostPlatform> {
+    ssh.platform.or_else(|| {
+        app.state::<HostCache>()
+            .platforms
+            .lock()
+            .expect("host cache poisoned")
+            .get(&host_id(&ssh.host, ssh.port()))
+            .copied()
+    })
+}
+
+async fn detect_platform(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+) -> Result<HostPlatform, String> {
+    let session = ssh_connect(app, ssh).await?.with_request(ctx.clone());
+    platform_of(&session).await
+}
+
+/// `uname` only succeeds on Unix-like hosts; Windows builds of it (Git Bash,
+/// MSYS, Cygwin) report their environment instead of an OS name.
+pub(crate) async fn platform_of(session: &SshSession) -> Result<HostPlatform, String> {
+    let res = session.exec_collect_full("uname -s").await?;
+    let name = res.stdout.trim().to_ascii_uppercase();
+    let windows_env = ["MINGW", "MSYS", "CYGWIN"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    Ok(if res.success() && !name.is_empty() && !windows_env {
+        HostPlatform::Posix
+    } else {
+        HostPlatform::Windows
+    })
+}
+
+/// Windows with OpenSSH and Windows PowerShell.
+pub(crate) struct WindowsHost {
+    vmrun: VmrunTarget,
+}
+
+impl WindowsHost {
+    fn password_line(password: Option<&str>) -> String {
+        password
+            .map(|pw| format!("$vmPassword = '{}'", ps_single_quote_escape(pw)))
+            .unwrap_or_default()
+    }
+
+    fn ps_bool(value: bool) -> &'static str {
+        if value {
+            "$true"
+        } else {
+            "$false"
+        }
+    }
+}
+
+impl RemoteHost for WindowsHost {
+    fn encode(&self, script: &str) -> String {
+        powershell_encoded(script)
+    }
+
+    /// Workstation and Player both ship vmrun; only Workstation has
+    /// `vmware.exe` next to it.
+    fn vmrun_detect_script(&self) -> String {
+        format!(
+            r#"
+{}
+$c=@()
+foreach($k in @('HKLM:\SOFTWARE\WOW6432Node\VMware, Inc.\VMware Workstation','HKLM:\SOFTWARE\VMware, Inc.\VMware Workstation','HKLM:\SOFTWARE\WOW6432Node\VMware, Inc.\VMware Player','HKLM:\SOFTWARE\VMware, Inc.\VMware Player')){{
+  $d=(Get-ItemProperty -LiteralPath $k -ErrorAction SilentlyContinue).InstallPath
+  if($d){{ $c+=(Join-Path $d 'vmrun.exe') }}
+}}
+$g=Get-Command vmrun.exe -CommandType Application -ErrorAction SilentlyContinue|Select-Object -First 1
+if($g){{ $c+=$g.Source }}
+$c+=@('C:\Program Files (x86)\VMware\VMware Workstation\vmrun.exe','C:\Program Files\VMware\VMware Workstation\vmrun.exe','C:\Program Files (x86)\VMware\VMware Player\vmrun.exe','C:\Program Files\VMware\VMware Player\vmrun.exe')
+$vmrun=$c|Where-Object{{ $_ -and (Test-Path -LiteralPath $_) }}|Select-Object -First 1
+if(-not $vmrun){{ 'vmrun.exe not found (checked the registry, PATH and VMware install dirs)'; exit 127 }}
+$vmrun
+if(Test-Path -LiteralPath (Join-Path (Split-Path -Parent $vmrun) 'vmware.exe')){{ 'ws' }}else{{ 'player' }}
+"#,
+            powershell_prelude()
+        )
+    }
+
+    /// `$vmrunArgLine` is the same arguments for scheduled task actions.
+    fn vmrun_locator(&self) -> String {
+        let args = self
+            .vmrun
+            .connection_args()
+            .into_iter()
+            .map(|(flag, value)| format!("'{flag}','{}'", ps_single_quote_escape(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"$vmrun='{path}';if(-not (Test-Path -LiteralPath $vmrun)){{throw "vmrun.exe not found at $vmrun (check the VMware settings for this host)"}}
+$vmrunArgs=@({args});$vmrunArgLine=($vmrunArgs|ForEach-Object{{'"'+$_+'"'}}) -join ' '"#,
+            path = ps_single_quote_escape(&self.vmrun.path),
+        )
+    }
+
+    fn vmrun_probe_script(&self) -> String {
+        format!(
+            "{}\n{}\n$vmrun\n(Get-Item -LiteralPath $vmrun).VersionInfo.ProductVersion",
+            powershell_prelude(),
+            self.vmrun_locator()
+        )
+    }
+
+    fn list_running_script(&self) -> String {
+        format!(
+            r#"
+{}
+{}
+$out = & $vmrun @vmrunArgs list 2>&1
+$code = $LASTEXITCODE
+if ($null -eq $code) {{ $code = 1 }}
+if ($code -ne 0) {{ if ($out) {{ $out }} else {{ 'vmrun failed with exit code ' + $code }}; exit $code }}
+$out
+"#,
+            powershell_prelude(),
+            self.vmrun_locator()
+        )
+    }
+
+    /// Password-protected VMs are started from a scheduled task so they run
+    /// in the interactive session rather than the SSH one.
+    fn start_script(&self, vmx_path: &str, password: Option<&str>, start_polls: u32) -> String {
+        format!(
+            r#"
+$ErrorActionPreference='Stop';$ProgressPreference='SilentlyContinue'
+{locator}
+$v='{vmx}'
+{pw_line}
+$hp={has_pw};$tn='tauri-app-vmstart-temp'
+function W{{for($i=1;$i-le30;$i++){{sleep 1;$o=&$vmrun @vmrunArgs list 2>&1;if($LASTEXITCODE-eq0 -and (($o-join"`n").IndexOf($v,[StringComparison]::OrdinalIgnoreCase)-ge0)){{return $true}}}}}}
+if(!$hp){{$a=$vmrunArgs+@('start',$v,'nogui');$o=&$vmrun @a 2>&1;$c=$LASTEXITCODE;if($null-eq$c){{$c=1}};if($c-ne0){{if($o){{$o}}else{{"vmrun start failed $c"}};exit $c}};$o;exit 0}}
+$arg=$vmrunArgLine+' -vp "'+$vmPassword+'" start "'+$v+'" nogui'
+try{{Unregister-ScheduledTask -TaskName $tn -Confirm:$false -ErrorAction SilentlyContinue|Out-Null;$tr=New-ScheduledTaskTrigger -Once -At (Get-Date).AddMinutes(1);$ac=New-ScheduledTaskAction -Execute $vmrun -Argument $arg;Register-ScheduledTask -TaskName $tn -Action $ac -Trigger $tr -Force|Out-Null;Start-ScheduledTask -TaskName $tn;"TASK started";$listed=$false;for($i=0;$i-lt{start_polls};$i++){{sleep 1;$lo=&$vmrun @vmrunArgs list 2>&1;if($LASTEXITCODE-eq0 -and (($lo-join"`n").IndexOf($v,[StringComparison]::OrdinalIgnoreCase)-ge0)){{$listed=$true;break}}}};if({cleanup}){{Unregister-ScheduledTask -TaskName $tn -Confirm:$false -ErrorAction SilentlyContinue|Out-Null}};if($listed){{exit 0}};"TASK not running; trying direct"}}catch{{"TASK failed";$_|Out-String}}
+$a=$vmrunArgs+@('-vp',$vmPassword,'start',$v,'nogui');$o=&$vmrun @a 2>&1;$c=$LASTEXITCODE;if($null-eq$c){{$c=1}};if($c-ne0){{if($o){{$o}}else{{"vmrun start failed $c"}};exit $c}};$o;exit 0
+"#,
+            locator = self.vmrun_locator(),
+            vmx = ps_single_quote_escape(vmx_path),
+            pw_line = Self::password_line(password),
+            has_pw = Self::ps_bool(password.is_some()),
+            start_polls = start_polls,
+            cleanup = Self::ps_bool(password.is_some()),
+        )
+    }
+
+    fn stop_script(
+        &self,
+        vmx_path: &str,
+        mode: &VmStopMode,
+        password: Option<&str>,
+        label: &str,
+    ) -> String {
+        format!(
+            r#"
+{prelude}
+{locator}
+$v='{vmx}'
+$m='{mode}'
+{pw_line}
+$a=@($vmrunArgs)
+if({has_password}){{ $a+=@('-vp',$vmPassword) }}
+$a+=@('stop',$v,$m)
+$o=& $vmrun @a 2>&1
+$c=$LASTEXITCODE
+if($null -eq $c){{ $c=1 }}
+"STOP {label} exit=$c"
+if($o){{ $o }}
+exit $c
+"#,
+            prelude = powershell_prelude(),
+            locator = self.vmrun_locator(),
+            vmx = ps_single_quote_escape(vmx_path),
+            mode = mode.as_str(),
+            pw_line = Self::password_line(password),
+            has_password = Self::ps_bool(password.is_some()),
+            label = label,
+        )
+    }
+
+    /// vmrun over SSH cannot stop a VM started from the desktop session; a
+    /// scheduled task runs in that session and can.
+    fn task_stop_script(
+        &self,
+        vmx_path: &str,
+        mode: &VmStopMode,
+        password: Option<&str>,
+    ) -> Option<String> {
+        Some(format!(
+            r#"
+{prelude}
+{locator}
+$v='{vmx}'
+$m='{mode}'
+{pw_line}
+$tn='tauri-vmstop-'+[guid]::NewGuid().ToString('N')
+$arg=$vmrunArgLine+' '
+if({has_password}){{ $arg+='-vp "'+$vmPassword+'" ' }}
+$arg+='stop "'+$v+'" '+$m
+$tr=New-ScheduledTaskTrigger -Once -At (Get-Date).AddMinutes(1)
+$ac=New-ScheduledTaskAction -Execute $vmrun -Argument $arg
+Register-ScheduledTask -TaskName $tn -Action $ac -Trigger $tr -Force|Out-Null
+Start-ScheduledTask -TaskName $tn
+"TASK started $tn"
+"#,
+            prelude = powershell_prelude(),
+            locator = self.vmrun_locator(),
+            vmx = ps_single_quote_escape(vmx_path),
+            mode = mode.as_str(),
+            pw_line = Self::password_line(password),
+            has_password = Self::ps_bool(password.is_some()),
+        ))
+    }
+
+    fn kill_vm_process_script(&self, vmx_path: &str) -> String {
+        format!(
+            r#"
+{prelude}
+$v='{vmx}'
+$matches=@(Get-CimInstance Win32_Process -Filter "Name = 'vmware-vmx.exe'" -ErrorAction SilentlyContinue | Where-Object {{
+  $cmd=$_.CommandLine
+  $cmd -and $cmd.IndexOf($v, [StringComparison]::OrdinalIgnoreCase) -ge 0
+}})
+if($matches.Count -eq 0){{ "No matching vmware-vmx.exe process found for $v"; exit 2 }}
+foreach($p in $matches){{
+  Stop-Process -Id $p.ProcessId -Force -ErrorAction Stop
+  "KILLED vmware-vmx.exe pid=$($p.ProcessId)"
+}}
+Start-Sleep -Seconds 2
+exit 0
+"#,
+            prelude = powershell_prelude(),
+            vmx = ps_single_quote_escape(vmx_path),
+        )
+    }
+
+    /// Children first; closing the channel alone leaves them running.
+    fn kill_process_tree_script(&self, command: &str) -> Option<String> {
+        let command = command.trim();
+        let marker = match command.rsplit_once("-EncodedCommand ") {
+            // The base64 payload is unique per script; its tail is plenty.
+            Some((_, encoded)) => &encoded[encoded.len().saturating_sub(64)..],
+            None => command,
+        };
+        if marker.is_empty() {
+            return None;
+        }
+        Some(format!(
+            r#"
+$ErrorActionPreference='SilentlyContinue'
+$m='{marker}'
+$all=@(Get-CimInstance Win32_Process)
+function K($id){{ $all|Where-Object {{ $_.ParentProcessId -eq $id }}|ForEach-Object {{ K $_.ProcessId }}; Stop-Process -Id $id -Force }}
+$all|Where-Object {{ $_.ProcessId -ne $PID -and $_.CommandLine -and $_.CommandLine.Contains($m) }}|ForEach-Object {{ K $_.ProcessId }}
+"#,
+            marker = ps_single_quote_escape(marker),
+        ))
+    }
+
+    fn sha256_script(&self, path: &str) -> String {
+        // Windows OpenSSH exposes drive paths as `/C:/...`.
+        let native = match path.strip_prefix('/') {
+            Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+            _ => path,
+        };
+        format!(
+            "$ErrorActionPreference='Stop';(Get-FileHash -Algorithm SHA256 -LiteralPath '{}').Hash",
+            ps_single_quote_escape(native)
+        )
+    }
+
+    fn scan_script(&self, roots: Option<&[String]>) -> Result<String, String> {
+        let Some(roots) = roots else {
+            return Ok(r#"
+$OutputEncoding=[Console]::OutputEncoding=[System.Text.UTF8Encoding]::new()
+$ProgressPreference = 'SilentlyContinue'
+$roots=@()
+if($env:USERPROFILE){ $roots += (Join-Path $env:USERPROFILE 'Documents\Virtual Machines') }
+if($env:PUBLIC){ $roots += (Join-Path $env:PUBLIC 'Documents\Shared Virtual Machines') }
+$roots = $roots | Where-Object { $_ -and (Test-Path -LiteralPath $_) } | Select-Object -Unique
+
+$paths=@()
+foreach($root in $roots){
+  $paths += Get-ChildItem -LiteralPath $root -Recurse -File -Filter *.vmx -ErrorAction SilentlyContinue |
+    Where-Object { $_.Extension -ieq '.vmx' } |
+    Select-Object -ExpandProperty FullName
+}
+
+$paths = $paths | Sort-Object -Unique | Select-Object -First 500
+@($paths) | ConvertTo-Json -Compress
+"#
+            .to_string());
+        };
+
+        let roots_json = serde_json::to_string(roots).map_err(|err| format!("{err:?}"))?;
+        Ok(format!(
+            r#"
+$OutputEncoding=[Console]::OutputEncoding=[System.Text.UTF8Encoding]::new()
+$ProgressPreference = 'SilentlyContinue'
+$inputRoots = '{roots_json}' | ConvertFrom-Json
+$roots=@()
+foreach($r in $inputRoots){{
+  if(-not $r){{ continue }}
+  $roots += [string]$r
+}}
+$roots = $roots | Select-Object -Unique
+
+$expanded=@()
+foreach($root in $roots){{
+  $resolved = $ExecutionContext.InvokeCommand.ExpandString($root)
+  if($resolved -and (Test-Path -LiteralPath $resolved)){{
+    $expanded += $resolved
+  }}
+}}
+$expanded = $expanded | Select-Object -Unique
+
+$paths=@()
+foreach($root in $expanded){{
+  $paths += Get-ChildItem -LiteralPath $root -Recurse -File -Filter *.vmx -ErrorAction SilentlyContinue |
+    Where-Object {{ $_.Extension -ieq '.vmx' }} |
+    Select-Object -ExpandProperty FullName
+}}
+
+$paths = $paths | Sort-Object -Unique | Select-Object -First 500
+@($paths) | ConvertTo-Json -Compress
+"#
+        ))
+    }
+
+    fn parse_paths(&self, output: &str) -> Result<Vec<String>, String> {
+        parse_json_string_array(output)
+    }
+
+    fn normalize_path(&self, path: &str) -> String {
+        normalize_vmx_key(path)
+    }
+
+    fn redact(&self, script: &str) -> String {
+        match &self.vmrun.server_password {
+            Some(pw) => script.replace(
+                &format!("'-p','{}'", ps_single_quote_escape(pw)),
+                "'-p','[REDACTED]'",
+            ),
+            None => script.to_string(),
+        }
+    }
+}
+
+/// Quotes `text` as a single POSIX shell word.
+pub(crate) fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+const POSIX_ENCODED_PREFIX: &str = "sh -c 'eval \"$(printf %s ";
+
+/// The base64 script inside a command built by `PosixHost::encode`.
+fn posix_payload(command: &str) -> Option<&str> {
+    command
+        .trim()
+        .strip_prefix(POSIX_ENCODED_PREFIX)?
+        .split_whitespace()
+        .next()
+}
+
+/// Linux (or another Unix) with VMware Workstation and a POSIX `sh`.
+pub(crate) struct PosixHost {
+    vmrun: VmrunTarget,
+}
+
+impl PosixHost {
+    /// Adds `-vp` to the connection arguments the locator left in `$@`.
+    fn vm_password_args(password: Option<&str>) -> String {
+        password
+            .map(|pw| format!("set -- \"$@\" -vp {}", sh_quote(pw)))
+            .unwrap_or_default()
+    }
+}
+
+impl RemoteHost for PosixHost {
+    /// Sent base64-encoded, like PowerShell's `-EncodedCommand`, so the login
+    /// shell (bash, zsh, fish, ...) never parses the script itself.
+    fn encode(&self, script: &str) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(script.trim());
+        format!("{POSIX_ENCODED_PREFIX}{encoded} | base64 -d)\"'")
+    }
+
+    /// Workstation installs `vmware`; Player only `vmplayer`.
+    fn vmrun_detect_script(&self) -> String {
+        r#"
+for p in "$(command -v vmrun 2>/dev/null)" /usr/bin/vmrun /usr/local/bin/vmrun /usr/lib/vmware/bin/vmrun; do
+  if [ -n "$p" ] && [ -x "$p" ]; then
+    echo "$p"
+    if command -v vmware >/dev/null 2>&1 || [ -x "$(dirname "$p")/vmware" ]; then echo ws; else echo player; fi
+    exit 0
+  fi
+done
+echo 'vmrun not found (checked PATH and VMware install dirs)' >&2
+exit 127
+"#
+        .to_string()
+    }
+
+    /// Leaves the connection arguments in `$@`.
+    fn vmrun_locator(&self) -> String {
+        let args = self
+            .vmrun
+            .connection_args()
+            .into_iter()
+            .map(|(flag, value)| format!("{flag} {}", sh_quote(value)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            r#"vmrun={path}
+if [ ! -x "$vmrun" ]; then echo "vmrun not found at $vmrun (check the VMware settings for this host)" >&2; exit 127; fi
+set -- {args}"#,
+            path = sh_quote(&self.vmrun.path),
+        )
+    }
+
+    fn vmrun_probe_script(&self) -> String {
+        format!(
+            r#"
+{}
+echo "$vmrun"
+"$vmrun" 2>&1 | sed -n 's/^vmrun version \([^ ]*\).*/\1/p' | head -n 1
+"#,
+            self.vmrun_locator()
+        )
+    }
+
+    fn list_running_script(&self) -> String {
+        format!(
+            r#"
+{}
+out=$("$vmrun" "$@" list 2>&1); code=$?
+if [ $code -ne 0 ]; then if [ -n "$out" ]; then printf '%s\n' "$out"; else echo "vmrun failed with exit code $code"; fi; exit $code; fi
+printf '%s\n' "$out"
+"#,
+            self.vmrun_locator()
+        )
+    }
+
+    /// No session juggling needed: vmrun started over SSH runs the VM
+    /// headless under the same user.
+    fn start_script(&self, vmx_path: &str, password: Option<&str>, _start_polls: u32) -> String {
+        format!(
+            r#"
+{locator}
+v={vmx}
+{args}
+o=$("$vmrun" "$@" start "$v" nogui 2>&1); c=$?
+if [ $c -ne 0 ]; then if [ -n "$o" ]; then printf '%s\n' "$o"; else echo "vmrun start failed $c"; fi; exit $c; fi
+printf '%s\n' "$o"
+"#,
+            locator = self.vmrun_locator(),
+            vmx = sh_quote(vmx_path),
+            args = Self::vm_password_args(password),
+        )
+    }
+
+    fn stop_script(
+        &self,
+        vmx_path: &str,
+        mode: &VmStopMode,
+        password: Option<&str>,
+        label: &str,
+    ) -> String {
+        format!(
+            r#"
+{locator}
+v={vmx}
+{args}
+o=$("$vmrun" "$@" stop "$v" {mode} 2>&1); c=$?
+echo "STOP {label} exit=$c"
+if [ -n "$o" ]; then printf '%s\n' "$o"; fi
+exit $c
+"#,
+            locator = self.vmrun_locator(),
+            vmx = sh_quote(vmx_path),
+            args = Self::vm_password_args(password),
+            mode = mode.as_str(),
+            label = label,
+        )
+    }
+
+    fn task_stop_script(
+        &self,
+        _vmx_path: &str,
+        _mode: &VmStopMode,
+        _password: Option<&str>,
+    ) -> Option<String> {
+        None
+    }
+
+    fn kill_vm_process_script(&self, vmx_path: &str) -> String {
+        format!(
+            r#"
+v={vmx}
+pids=$(ps -eo pid=,comm=,args= | awk -v v="$v" '$2 ~ /^vmware-vmx/ && index($0, v) {{ print $1 }}')
+if [ -z "$pids" ]; then echo "No matching vmware-vmx process found for $v"; exit 2; fi
+for p in $pids; do
+  kill -9 "$p" || exit 1
+  echo "KILLED vmware-vmx pid=$p"
+done
+sleep 2
+exit 0
+"#,
+            vmx = sh_quote(vmx_path),
+        )
+    }
+
+    fn kill_process_tree_script(&self, command: &str) -> Option<String> {
+        let payload = posix_payload(command)?;
+        let marker = &payload[payload.len().saturating_sub(64)..];
+        if marker.is_empty() {
+            return None;
+        }
+        Some(format!(
+            r#"
+m={marker}
+kill_tree() {{ for c in $(pgrep -P "$1" 2>/dev/null); do kill_tree "$c"; done; kill -9 "$1" 2>/dev/null; }}
+ps -eo pid=,args= | while read -r pid args; do
+  [ "$pid" = "$$" ] && continue
+  case $args in *"$m"*) kill_tree "$pid";; esac
+done
+exit 0
+"#,
+            marker = sh_quote(marker),
+        ))
+    }
+
+    fn sha256_script(&self, path: &str) -> String {
+        format!("sha256sum -- {}", sh_quote(path))
+    }
+
+    fn scan_script(&self, roots: Option<&[String]>) -> Result<String, String> {
+        let roots = match roots {
+            Some(roots) => roots
+                .iter()
+                .filter(|root| !root.trim().is_empty())
+                .map(|root| sh_quote(root.trim()))
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => r#""$HOME/vmware" "$HOME/Virtual Machines""#.to_string(),
+        };
+        Ok(format!(
+            r#"
+for r in {roots}; do
+  case $r in
+    "~") r=$HOME ;;
+    "~/"*) r=$HOME/${{r#"~/"}} ;;
+    '$HOME/'*) r=$HOME/${{r#'$HOME/'}} ;;
+  esac
+  [ -d "$r" ] && find "$r" -type f -iname '*.vmx' 2>/dev/null
+done | sort -u | head -n 500
+"#
+        ))
+    }
+
+    fn parse_paths(&self, output: &str) -> Result<Vec<String>, String> {
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Case-sensitive, `/`-separated; only stray quotes and whitespace go.
+    fn normalize_path(&self, path: &str) -> String {
+        path.trim().trim_matches('"').to_string()
+    }
+
+    fn redact(&self, script: &str) -> String {
+        match &self.vmrun.server_password {
+            Some(pw) => script.replace(&format!("-p {}", sh_quote(pw)), "-p '[REDACTED]'"),
+            None => script.to_string(),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::timeouts::{self, TimeoutProfile};
+use crate::{
+    now_ms, ssh_config, ssh_connect, truncate_text, ExecCollected, RequestContext, SshConfig,
+    TraceEntry, TraceStore, REQUEST_CANCELLED,
+};
+
+/// Prefix on errors worth retrying: the host or network may come back.
+pub(crate) const SSH_TRANSIENT: &str = "SSH_TRANSIENT";
+
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+fn transient(message: impl std::fmt::Display) -> String {
+    format!("{SSH_TRANSIENT}: {message}")
+}
+
+pub(crate) fn is_retryable(err: &str) -> bool {
+    err.contains(SSH_TRANSIENT)
+}
+
+fn io_error_is_transient(err: &std::io::Error) -> bool {
+    match err.kind() {
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::BrokenPipe
+        | ErrorKind::TimedOut
+        | ErrorKind::UnexpectedEof
+        | ErrorKind::Interrupted
+        | ErrorKind::HostUnreachable
+        | ErrorKind::NetworkUnreachable
+        | ErrorKind::NetworkDown
+        | ErrorKind::AddrNotAvailable => true,
+        // DNS failures surface without a dedicated kind.
+        _ => err
+            .to_string()
+            .to_lowercase()
+            .contains("temporary failure in name resolution"),
+    }
+}
+
+/// Connection-level failures that a later attempt may not hit. Protocol,
+/// key and authentication errors are fatal.
+fn russh_error_is_transient(err: &russh::Error) -> bool {
+    match err {
+        russh::Error::IO(io) => io_error_is_transient(io),
+        russh::Error::Disconnect
+        | russh::Error::HUP
+        | russh::Error::ConnectionTimeout
+        | russh::Error::KeepaliveTimeout
+        | russh::Error::InactivityTimeout
+        | russh::Error::SendError
+        | russh::Error::Elapsed(_) => true,
+        russh::Error::ChannelOpenFailure(reason) => {
+            matches!(reason, russh::ChannelOpenFailure::ResourceShortage)
+        }
+        _ => false,
+    }
+}
+
+/// Error text for a russh failure, tagged with `SSH_TRANSIENT` when retrying
+/// could help.
+pub(crate) fn describe_russh_error(err: &russh::Error) -> String {
+    if russh_error_is_transient(err) {
+        transient(err)
+    } else {
+        format!("{err:?}")
+    }
+}
+
+/// Error for a connect attempt that ran out of time.
+pub(crate) fn connect_timed_out(host: &str, port: u16, timeout: Duration) -> String {
+    transient(format!(
+        "Timed out connecting to {host}:{port} after {}s",
+        timeout.as_secs()
+    ))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    attempts: u32,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn from_profile(profile: &TimeoutProfile) -> Self {
+        Self {
+            attempts: profile.retry_attempts.max(1),
+            base_delay: Duration::from_millis(profile.retry_base_delay_ms),
+        }
+    }
+
+    /// Full jitter: uniform in `[0, min(MAX_BACKOFF, base * 2^retry))`, so
+    /// clients retrying after the same blip spread out.
+    fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .base_delay
+            .saturating_mul(1u32 << retry.min(16))
+            .min(MAX_BACKOFF);
+        let cap_ms = cap.as_millis() as u64;
+        if cap_ms == 0 {
+            return Duration::ZERO;
+        }
+        let random = RandomState::new().build_hasher().finish();
+        Duration::from_millis(random % cap_ms)
+    }
+}
+
+/// Policy from the effective timeout profile of the (alias-resolved) host.
+pub(crate) fn policy_for(app: &AppHandle, ssh: &SshConfig) -> Result<RetryPolicy, String> {
+    let cfg = ssh_config::resolve_alias(app, ssh)?;
+    let profile = timeouts::profile_for(app, &cfg.host, cfg.port())?;
+    Ok(RetryPolicy::from_profile(&profile))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RetryAttempt {
+    attempt: u32,
+    duration_ms: u64,
+    error: Option<String>,
+    /// Wait before the next attempt; `None` when there was none.
+    backoff_ms: Option<u64>,
+}
+
+/// Runs `op` until it succeeds, fails with a non-retryable error or the
+/// policy's attempts run out. Returns the last result and every attempt.
+pub(crate) async fn with_retry<T, F, Fut>(
+    policy: RetryPolicy,
+    mut op: F,
+) -> (Result<T, String>, Vec<RetryAttempt>)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let mut attempts = Vec::new();
+    let mut attempt = 1;
+    loop {
+        let started = Instant::now();
+        let res = op().await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match res {
+            Err(err) if is_retryable(&err) && attempt < policy.attempts => {
+                let backoff = policy.backoff(attempt - 1);
+                attempts.push(RetryAttempt {
+                    attempt,
+                    duration_ms,
+                    error: Some(truncate_text(&err, 1024)),
+                    backoff_ms: Some(backoff.as_millis() as u64),
+                });
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            res => {
+                attempts.push(RetryAttempt {
+                    attempt,
+                    duration_ms,
+                    error: res.as_ref().err().map(|err| truncate_text(err, 1024)),
+                    backoff_ms: None,
+                });
+                return (res, attempts);
+            }
+        }
+    }
+}
+
+/// Connects and runs an idempotent command under the host's retry policy.
+///
+/// When every attempt fails there is no command result to trace, so the
+/// failure and its attempts are traced here; on success the caller traces
+/// the result along with the returned attempts.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn exec_with_retry(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: &RequestContext,
+    action: &str,
+    command_log: &str,
+    exec_command: &str,
+    request_id: &Option<String>,
+) -> Result<(ExecCollected, Vec<RetryAttempt>), String> {
+    let policy = policy_for(app, ssh)?;
+    let started = Instant::now();
+    let (res, attempts) = with_retry(policy, || async {
+        let session = ssh_connect(app, ssh).await?.with_request(ctx.clone());
+        session.exec_collect_full(exec_command).await
+    })
+    .await;
+
+    match res {
+        Ok(res) => Ok((res, attempts)),
+        Err(err) => {
+            store.push(TraceEntry {
+                id: 0,
+                at: now_ms(),
+                action: action.to_string(),
+                ok: false,
+                cancelled: err == REQUEST_CANCELLED,
+                duration_ms: started.elapsed().as_millis() as u64,
+                command: truncate_text(command_log, 16 * 1024),
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_status: None,
+                exit_signal: None,
+                truncated: false,
+                output_id: None,
+                attempts,
+                error: Some(truncate_text(&err, 8 * 1024)),
+                request_id: request_id.clone(),
+            });
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,610 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use russh_sftp::client::SftpSession;
+use russh_sftp::protocol::OpenFlags;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::remote_host;
+use crate::vmrun::VmrunTarget;
+use crate::{now_ms, ssh_connect, truncate_text, SshConfig, SshSession, TraceEntry, TraceStore};
+
+const CHUNK_SIZE: usize = 256 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+pub(crate) const SFTP_PROGRESS_EVENT: &str = "sftp-progress";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SftpEntry {
+    path: String,
+    size: u64,
+    is_dir: bool,
+    is_symlink: bool,
+    permissions: Option<u32>,
+    modified_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SftpTransfer {
+    local_path: String,
+    remote_path: String,
+    size: u64,
+    /// Offset the transfer continued from; 0 for a fresh transfer.
+    resumed_from: u64,
+    sha256: String,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SftpProgress {
+    request_id: Option<String>,
+    direction: &'static str,
+    remote_path: String,
+    transferred: u64,
+    total: u64,
+    done: bool,
+}
+
+/// Throttled `sftp-progress` emitter for one transfer.
+struct ProgressReporter<'a> {
+    app: &'a AppHandle,
+    request_id: Option<String>,
+    direction: &'static str,
+    remote_path: String,
+    total: u64,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressReporter<'_> {
+    fn report(&mut self, transferred: u64) {
+        let done = transferred >= self.total;
+        if !done
+            && self
+                .last_emit
+                .is_some_and(|at| at.elapsed() < PROGRESS_INTERVAL)
+        {
+            return;
+        }
+        self.last_emit = Some(Instant::now());
+        let _ = self.app.emit(
+            SFTP_PROGRESS_EVENT,
+            SftpProgress {
+                request_id: self.request_id.clone(),
+                direction: self.direction,
+                remote_path: self.remote_path.clone(),
+                transferred,
+                total: self.total,
+                done,
+            },
+        );
+    }
+}
+
+fn sftp_err(err: russh_sftp::client::error::Error) -> String {
+    format!("SFTP: {err}")
+}
+
+async fn sftp_open(session: &SshSession) -> Result<SftpSession, String> {
+    let channel = session
+        .session
+        .channel_open_session()
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    channel
+        .request_subsystem(true, "sftp")
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    SftpSession::new(channel.into_stream())
+        .await
+        .map_err(sftp_err)
+}
+
+fn validate_remote_path(path: &str) -> Result<String, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Remote path cannot be empty.".to_string());
+    }
+    if path.contains(['\n', '\r', '\0']) {
+        return Err("Remote path contains unsupported characters".to_string());
+    }
+    Ok(path.to_string())
+}
+
+fn part_path(path: &str) -> String {
+    format!("{path}.part")
+}
+
+fn local_part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+async fn sha256_of<R: AsyncRead + Unpin>(mut reader: R) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+fn parse_sha256(output: &str) -> Option<String> {
+    let token = output.split_whitespace().next()?.to_ascii_lowercase();
+    (token.len() == 64 && token.bytes().all(|b| b.is_ascii_hexdigit())).then_some(token)
+}
+
+/// SHA-256 of a remote file. Hashing on the host avoids reading the file back;
+/// when the host cannot hash it we fall back to SFTP.
+async fn remote_sha256(
+    session: &SshSession,
+    sftp: &SftpSession,
+    path: &str,
+) -> Result<String, String> {
+    let platform = match session.platform {
+        Some(platform) => Ok(platform),
+        None => remote_host::platform_of(session).await,
+    };
+    if let Ok(platform) = platform {
+        // Hashing needs no vmrun, so none is configured.
+        let host = platform.host(VmrunTarget::default());
+        if let Ok(res) = session
+            .exec_collect_full(&host.encode(&host.sha256_script(path)))
+            .await
+        {
+            if res.success() {
+                if let Some(hash) = parse_sha256(&res.stdout) {
+                    return Ok(hash);
+                }
+            }
+        }
+    }
+
+    let file = sftp.open(path).await.map_err(sftp_err)?;
+    sha256_of(file).await
+}
+
+/// Copies `reader` into `writer` in fixed-size chunks, reporting progress.
+async fn copy_chunked<R, W>(
+    mut reader: R,
+    mut writer: W,
+    mut transferred: u64,
+    progress: &mut ProgressReporter<'_>,
+) -> Result<u64, String>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    progress.report(transferred);
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        if n == 0 {
+            break;
+        }
+        writer
+            .write_all(&buf[..n])
+            .await
+            .map_err(|err| format!("{err:?}"))?;
+        transferred += n as u64;
+        progress.report(transferred);
+    }
+    writer.flush().await.map_err(|err| format!("{err:?}"))?;
+    writer.shutdown().await.map_err(|err| format!("{err:?}"))?;
+    Ok(transferred)
+}
+
+fn push_transfer_trace(
+    store: &TraceStore,
+    action: &str,
+    command: String,
+    started: Instant,
+    result: &Result<SftpTransfer, String>,
+    request_id: Option<String>,
+) {
+    let (ok, output, error) = match result {
+        Ok(t) => (
+            true,
+            format!(
+                "size={} resumed_from={} sha256={}",
+                t.size, t.resumed_from, t.sha256
+            ),
+            None,
+        ),
+        Err(err) => (false, String::new(), Some(truncate_text(err, 8 * 1024))),
+    };
+    store.push(TraceEntry {
+        id: 0,
+        at: now_ms(),
+        action: action.to_string(),
+        ok,
+        cancelled: false,
+        duration_ms: started.elapsed().as_millis() as u64,
+        command,
+        stdout: output,
+        stderr: String::new(),
+        exit_status: None,
+        exit_signal: None,
+        truncated: false,
+        output_id: None,
+        attempts: Vec::new(),
+        error,
+        request_id,
+    });
+}
+
+#[tauri::command]
+pub(crate) async fn sftp_stat(
+    app: AppHandle,
+    ssh: SshConfig,
+    path: String,
+) -> Result<SftpEntry, String> {
+    let path = validate_remote_path(&path)?;
+    let session = ssh_connect(&app, &ssh).await?;
+    let sftp = sftp_open(&session).await?;
+    let result = stat(&sftp, path).await;
+    let _ = sftp.close().await;
+    result
+}
+
+async fn stat(sftp: &SftpSession, path: String) -> Result<SftpEntry, String> {
+    let meta = sftp
+        .symlink_metadata(path.clone())
+        .await
+        .map_err(sftp_err)?;
+    let is_symlink = meta.file_type().is_symlink();
+    // Report what a symlink points at, like `stat` would.
+    let meta = if is_symlink {
+        sftp.metadata(path.clone()).await.unwrap_or(meta)
+    } else {
+        meta
+    };
+    Ok(SftpEntry {
+        path,
+        size: meta.len(),
+        is_dir: meta.file_type().is_dir(),
+        is_symlink,
+        permissions: meta.permissions.map(|p| p & 0o7777),
+        modified_at: meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64),
+    })
+}
+
+#[tauri::command]
+pub(crate) async fn sftp_mkdir(
+    app: AppHandle,
+    ssh: SshConfig,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<(), String> {
+    let path = validate_remote_path(&path)?;
+    let session = ssh_connect(&app, &ssh).await?;
+    let sftp = sftp_open(&session).await?;
+
+    let result = if recursive.unwrap_or(false) {
+        let mut current = String::new();
+        let mut result = Ok(());
+        for part in path.split('/') {
+            if part.is_empty() {
+                if current.is_empty() {
+                    current.push('/');
+                }
+                continue;
+            }
+            if !current.is_empty() && !current.ends_with('/') {
+                current.push('/');
+            }
+            current.push_str(part);
+            let created = match sftp.try_exists(current.clone()).await {
+                Ok(true) => Ok(()),
+                Ok(false) => sftp.create_dir(current.clone()).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = created {
+                result = Err(sftp_err(err));
+                break;
+            }
+        }
+        result
+    } else {
+        sftp.create_dir(path).await.map_err(sftp_err)
+    };
+    let _ = sftp.close().await;
+    result
+}
+
+#[tauri::command]
+pub(crate) async fn sftp_delete(
+    app: AppHandle,
+    ssh: SshConfig,
+    path: String,
+) -> Result<(), String> {
+    let path = validate_remote_path(&path)?;
+    let session = ssh_connect(&app, &ssh).await?;
+    let sftp = sftp_open(&session).await?;
+    // Directories must be empty; we never delete trees on the host recursively.
+    let result = match sftp.symlink_metadata(path.clone()).await {
+        Ok(meta) if meta.file_type().is_dir() => sftp.remove_dir(path).await,
+        Ok(_) => sftp.remove_file(path).await,
+        Err(err) => Err(err),
+    };
+    let _ = sftp.close().await;
+    result.map_err(sftp_err)
+}
+
+/// Uploads via `<remote>.part`, continuing an earlier partial upload when the
+/// part file is still there, then verifies the SHA-256 before renaming it.
+#[tauri::command]
+pub(crate) async fn sftp_upload(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    local_path: String,
+    remote_path: String,
+    resume: Option<bool>,
+    request_id: Option<String>,
+) -> Result<SftpTransfer, String> {
+    let started = Instant::now();
+    let command = format!("upload {local_path} -> {remote_path}");
+    let result = upload(&app, &ssh, &local_path, &remote_path, resume, &request_id).await;
+    push_transfer_trace(&store, "sftp_upload", command, started, &result, request_id);
+    result
+}
+
+async fn upload(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    local_path: &str,
+    remote_path: &str,
+    resume: Option<bool>,
+    request_id: &Option<String>,
+) -> Result<SftpTransfer, String> {
+    let started = Instant::now();
+    let remote_path = validate_remote_path(remote_path)?;
+    let local = PathBuf::from(local_path.trim());
+    let size = tokio::fs::metadata(&local)
+        .await
+        .map_err(|err| format!("Cannot read {}: {err}", local.display()))?
+        .len();
+
+    let session = ssh_connect(app, ssh).await?;
+    let sftp = sftp_open(&session).await?;
+    let result = upload_via(
+        app,
+        &session,
+        &sftp,
+        &local,
+        &remote_path,
+        size,
+        resume,
+        request_id,
+    )
+    .await;
+    let _ = sftp.close().await;
+    result.map(|(resumed_from, sha256)| SftpTransfer {
+        local_path: local.display().to_string(),
+        remote_path,
+        size,
+        resumed_from,
+        sha256,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Fills `<remote>.part`, verifies it and moves it into place; returns the
+/// resume offset and hash.
+#[allow(clippy::too_many_arguments)]
+async fn upload_via(
+    app: &AppHandle,
+    session: &SshSession,
+    sftp: &SftpSession,
+    local: &Path,
+    remote_path: &str,
+    size: u64,
+    resume: Option<bool>,
+    request_id: &Option<String>,
+) -> Result<(u64, String), String> {
+    let part = part_path(remote_path);
+
+    let existing = match sftp.metadata(part.clone()).await {
+        Ok(meta) if resume.unwrap_or(true) && meta.len() <= size => meta.len(),
+        _ => 0,
+    };
+    let flags = if existing > 0 {
+        OpenFlags::WRITE
+    } else {
+        OpenFlags::CREATE | OpenFlags::TRUNCATE | OpenFlags::WRITE
+    };
+    let mut remote = sftp
+        .open_with_flags(part.clone(), flags)
+        .await
+        .map_err(sftp_err)?;
+    remote
+        .seek(std::io::SeekFrom::Start(existing))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let mut file = tokio::fs::File::open(local)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    file.seek(std::io::SeekFrom::Start(existing))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+
+    let mut progress = ProgressReporter {
+        app,
+        request_id: request_id.clone(),
+        direction: "upload",
+        remote_path: remote_path.to_string(),
+        total: size,
+        last_emit: None,
+    };
+    copy_chunked(file, remote, existing, &mut progress).await?;
+
+    let local_hash = sha256_of(
+        tokio::fs::File::open(local)
+            .await
+            .map_err(|err| format!("{err:?}"))?,
+    )
+    .await?;
+    let remote_hash = remote_sha256(session, sftp, &part).await?;
+    if local_hash != remote_hash {
+        let _ = sftp.remove_file(part).await;
+        return Err(format!(
+            "Checksum mismatch after upload (local {local_hash}, remote {remote_hash}); partial file discarded"
+        ));
+    }
+
+    if sftp.try_exists(remote_path).await.unwrap_or(false) {
+        sftp.remove_file(remote_path).await.map_err(sftp_err)?;
+    }
+    sftp.rename(part, remote_path).await.map_err(sftp_err)?;
+    Ok((existing, local_hash))
+}
+
+/// Downloads via `<local>.part`, resuming from its length when present, and
+/// verifies the SHA-256 before moving it into place.
+#[tauri::command]
+pub(crate) async fn sftp_download(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    remote_path: String,
+    local_path: String,
+    resume: Option<bool>,
+    request_id: Option<String>,
+) -> Result<SftpTransfer, String> {
+    let started = Instant::now();
+    let command = format!("download {remote_path} -> {local_path}");
+    let result = download(&app, &ssh, &remote_path, &local_path, resume, &request_id).await;
+    push_transfer_trace(
+        &store,
+        "sftp_download",
+        command,
+        started,
+        &result,
+        request_id,
+    );
+    result
+}
+
+async fn download(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    remote_path: &str,
+    local_path: &str,
+    resume: Option<bool>,
+    request_id: &Option<String>,
+) -> Result<SftpTransfer, String> {
+    let started = Instant::now();
+    let remote_path = validate_remote_path(remote_path)?;
+    let local = PathBuf::from(local_path.trim());
+    let part = local_part_path(&local);
+
+    let session = ssh_connect(app, ssh).await?;
+    let sftp = sftp_open(&session).await?;
+    let result = download_via(
+        app,
+        &session,
+        &sftp,
+        &remote_path,
+        &part,
+        resume,
+        request_id,
+    )
+    .await;
+    let _ = sftp.close().await;
+    let (size, resumed_from, sha256) = result?;
+
+    tokio::fs::rename(&part, &local)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+
+    Ok(SftpTransfer {
+        local_path: local.display().to_string(),
+        remote_path,
+        size,
+        resumed_from,
+        sha256,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Fills `part` and verifies it; returns the size, resume offset and hash.
+async fn download_via(
+    app: &AppHandle,
+    session: &SshSession,
+    sftp: &SftpSession,
+    remote_path: &str,
+    part: &Path,
+    resume: Option<bool>,
+    request_id: &Option<String>,
+) -> Result<(u64, u64, String), String> {
+    let size = sftp.metadata(remote_path).await.map_err(sftp_err)?.len();
+
+    let existing = match tokio::fs::metadata(part).await {
+        Ok(meta) if resume.unwrap_or(true) && meta.len() <= size => meta.len(),
+        _ => 0,
+    };
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(existing == 0)
+        .open(part)
+        .await
+        .map_err(|err| format!("Cannot write {}: {err}", part.display()))?;
+    file.seek(std::io::SeekFrom::Start(existing))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let mut remote = sftp.open(remote_path).await.map_err(sftp_err)?;
+    remote
+        .seek(std::io::SeekFrom::Start(existing))
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+
+    let mut progress = ProgressReporter {
+        app,
+        request_id: request_id.clone(),
+        direction: "download",
+        remote_path: remote_path.to_string(),
+        total: size,
+        last_emit: None,
+    };
+    copy_chunked(remote, file, existing, &mut progress).await?;
+
+    let local_hash = sha256_of(
+        tokio::fs::File::open(part)
+            .await
+            .map_err(|err| format!("{err:?}"))?,
+    )
+    .await?;
+    let remote_hash = remote_sha256(session, sftp, remote_path).await?;
+    if local_hash != remote_hash {
+        let _ = tokio::fs::remove_file(part).await;
+        return Err(format!(
+            "Checksum mismatch after download (local {local_hash}, remote {remote_hash}); partial file discarded"
+        ));
+    }
+    Ok((size, existing, local_hash))
+}
@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use russh::client;
+use russh::{ChannelMsg, ChannelWriteHalf};
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{now_ms, ssh_connect, SshConfig, SshSession};
+
+pub(crate) const SHELL_OUTPUT_EVENT: &str = "shell-output";
+pub(crate) const SHELL_EXIT_EVENT: &str = "shell-exit";
+
+const DEFAULT_TERM: &str = "xterm-256color";
+const DEFAULT_COLS: u32 = 120;
+const DEFAULT_ROWS: u32 = 30;
+
+struct ShellSession {
+    ssh: SshConfig,
+    term: String,
+    cols: u32,
+    rows: u32,
+    created_at: u64,
+    // Held so the pool sees the connection as in use while the shell is open.
+    _session: SshSession,
+    channel: Arc<ChannelWriteHalf<client::Msg>>,
+    reader: JoinHandle<()>,
+}
+
+impl ShellSession {
+    fn info(&self, id: u64) -> ShellInfo {
+        ShellInfo {
+            id,
+            ssh_host: self.ssh.host.clone(),
+            ssh_port: self.ssh.port(),
+            ssh_user: self.ssh.user.clone(),
+            term: self.term.clone(),
+            cols: self.cols,
+            rows: self.rows,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// Interactive PTY shells, each on its own SSH channel.
+#[derive(Default)]
+pub(crate) struct ShellSessions {
+    next_id: AtomicU64,
+    shells: Mutex<BTreeMap<u64, ShellSession>>,
+}
+
+impl ShellSessions {
+    fn channel(&self, id: u64) -> Result<Arc<ChannelWriteHalf<client::Msg>>, String> {
+        let guard = self.shells.lock().expect("shell sessions poisoned");
+        guard
+            .get(&id)
+            .map(|shell| shell.channel.clone())
+            .ok_or_else(|| format!("Shell not found: {id}"))
+    }
+
+    /// Drops every shell; called when the app exits.
+    pub(crate) fn close_all(&self) {
+        let mut guard = self.shells.lock().expect("shell sessions poisoned");
+        for shell in guard.values() {
+            shell.reader.abort();
+        }
+        guard.clear();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShellInfo {
+    id: u64,
+    ssh_host: String,
+    ssh_port: u16,
+    ssh_user: String,
+    term: String,
+    cols: u32,
+    rows: u32,
+    created_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShellOutput {
+    shell_id: u64,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShellExit {
+    shell_id: u64,
+    exit_status: Option<u32>,
+}
+
+/// Splits off the longest prefix of `pending` that can be emitted as text.
+///
+/// A UTF-8 sequence cut at the end of a packet is kept for the next one;
+/// bytes that can never be valid are replaced rather than stalling the stream.
+fn take_text(pending: &mut Vec<u8>) -> String {
+    let cut = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let chunk: Vec<u8> = pending.drain(..cut).collect();
+    String::from_utf8_lossy(&chunk).into_owned()
+}
+
+fn validate_size(cols: u32, rows: u32) -> Result<(), String> {
+    if cols == 0 || rows == 0 || cols > 1000 || rows > 1000 {
+        return Err(format!("Invalid terminal size: {cols}x{rows}"));
+    }
+    Ok(())
+}
+
+/// Forwards the channel's output until the remote shell exits or the channel
+/// closes, then forgets the shell.
+async fn pump_output(app: AppHandle, id: u64, mut reader: russh::ChannelReadHalf) {
+    let mut pending = Vec::new();
+    let mut exit_status = None;
+    while let Some(msg) = reader.wait().await {
+        match msg {
+            // A PTY merges stderr into stdout; extended data is rare but kept.
+            ChannelMsg::Data { data } | ChannelMsg::ExtendedData { data, .. } => {
+                pending.extend_from_slice(data.as_ref());
+                let text = take_text(&mut pending);
+                if !text.is_empty() {
+                    let _ = app.emit(
+                        SHELL_OUTPUT_EVENT,
+                        ShellOutput {
+                            shell_id: id,
+                            data: text,
+                        },
+                    );
+                }
+            }
+            ChannelMsg::ExitStatus {
+                exit_status: status,
+            } => exit_status = Some(status),
+            _ => {}
+        }
+    }
+    if !pending.is_empty() {
+        let _ = app.emit(
+            SHELL_OUTPUT_EVENT,
+            ShellOutput {
+                shell_id: id,
+                data: String::from_utf8_lossy(&pending).into_owned(),
+            },
+        );
+    }
+    app.state::<ShellSessions>()
+        .shells
+        .lock()
+        .expect("shell sessions poisoned")
+        .remove(&id);
+    let _ = app.emit(
+        SHELL_EXIT_EVENT,
+        ShellExit {
+            shell_id: id,
+            exit_status,
+        },
+    );
+}
+
+#[tauri::command]
+pub(crate) async fn shell_open(
+    app: AppHandle,
+    shells: tauri::State<'_, ShellSessions>,
+    ssh: SshConfig,
+    cols: Option<u32>,
+    rows: Option<u32>,
+    term: Option<String>,
+) -> Result<ShellInfo, String> {
+    let cols = cols.unwrap_or(DEFAULT_COLS);
+    let rows = rows.unwrap_or(DEFAULT_ROWS);
+    validate_size(cols, rows)?;
+    let term = term
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| DEFAULT_TERM.to_string());
+
+    let session = ssh_connect(&app, &ssh).await?;
+    let channel = session
+        .session
+        .channel_open_session()
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    channel
+        .request_pty(true, &term, cols, rows, 0, 0, &[])
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    channel
+        .request_shell(true)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    let (reader, channel) = channel.split();
+
+    let id = shells.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    // Insert before the reader starts so a shell that exits immediately is
+    // still removed by it.
+    let mut guard = shells.shells.lock().expect("shell sessions poisoned");
+    let reader = tauri::async_runtime::spawn(pump_output(app.clone(), id, reader));
+    let shell = ShellSession {
+        ssh,
+        term,
+        cols,
+        rows,
+        created_at: now_ms(),
+        _session: session,
+        channel: Arc::new(channel),
+        reader,
+    };
+    let info = shell.info(id);
+    guard.insert(id, shell);
+    Ok(info)
+}
+
+#[tauri::command]
+pub(crate) async fn shell_write(
+    shells: tauri::State<'_, ShellSessions>,
+    id: u64,
+    data: String,
+) -> Result<(), String> {
+    let channel = shells.channel(id)?;
+    channel
+        .data(data.as_bytes())
+        .await
+        .map_err(|err| format!("{err:?}"))
+}
+
+#[tauri::command]
+pub(crate) async fn shell_resize(
+    shells: tauri::State<'_, ShellSessions>,
+    id: u64,
+    cols: u32,
+    rows: u32,
+) -> Result<(), String> {
+    validate_size(cols, rows)?;
+    let channel = shells.channel(id)?;
+    channel
+        .window_change(cols, rows, 0, 0)
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    if let Some(shell) = shells
+        .shells
+        .lock()
+        .expect("shell sessions poisoned")
+        .get_mut(&id)
+    {
+        shell.cols = cols;
+        shell.rows = rows;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn shell_list(
+    shells: tauri::State<'_, ShellSessions>,
+) -> Result<Vec<ShellInfo>, String> {
+    let guard = shells.shells.lock().expect("shell sessions poisoned");
+    Ok(guard.iter().map(|(id, shell)| shell.info(*id)).collect())
+}
+
+#[tauri::command]
+pub(crate) async fn shell_close(
+    shells: tauri::State<'_, ShellSessions>,
+    id: u64,
+) -> Result<(), String> {
+    let channel = shells.channel(id)?;
+    // The reader sees the close, emits `shell-exit` and removes the shell.
+    channel.close().await.map_err(|err| format!("{err:?}"))
+}
@@ -0,0 +1,634 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{default_ssh_port, SshAuthMethod, SshConfig, SshJumpHost};
+
+// Bounds Include nesting and ProxyJump chains so a cyclic config cannot hang us.
+const MAX_DEPTH: usize = 16;
+
+/// One `Host` block; options before the first `Host` line apply to every host.
+#[derive(Debug, Clone)]
+struct HostBlock {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl HostBlock {
+    fn matches(&self, alias: &str) -> bool {
+        let mut matched = false;
+        for pattern in &self.patterns {
+            if let Some(negated) = pattern.strip_prefix('!') {
+                if glob_match(negated, alias) {
+                    return false;
+                }
+            } else if glob_match(pattern, alias) {
+                matched = true;
+            }
+        }
+        matched
+    }
+}
+
+/// Parsed OpenSSH client configuration (`~/.ssh/config` and its Includes).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OpenSshConfig {
+    blocks: Vec<HostBlock>,
+    home: PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+struct HostOptions {
+    host_name: Option<String>,
+    port: Option<u16>,
+    user: Option<String>,
+    identity_file: Option<String>,
+    proxy_jump: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SshConfigHost {
+    alias: String,
+    ssh: SshConfig,
+}
+
+/// `*` / `?` glob match, case-insensitive like OpenSSH host patterns.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn inner(p: &[char], t: &[char]) -> bool {
+        match p.split_first() {
+            None => t.is_empty(),
+            Some(('*', rest)) => (0..=t.len()).any(|i| inner(rest, &t[i..])),
+            Some(('?', rest)) => !t.is_empty() && inner(rest, &t[1..]),
+            Some((c, rest)) => t.first() == Some(c) && inner(rest, &t[1..]),
+        }
+    }
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    inner(&p, &t)
+}
+
+/// Splits a config line into arguments, honoring double quotes.
+fn split_args(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for ch in text.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Splits `Keyword value` / `Keyword=value` into a lowercased keyword and its arguments.
+fn split_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let key_end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let (key, rest) = line.split_at(key_end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    Some((key.to_ascii_lowercase(), rest.to_string()))
+}
+
+fn expand_tilde(path: &str, home: &Path) -> PathBuf {
+    if path == "~" {
+        return home.to_path_buf();
+    }
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Files matched by an Include argument; wildcards are honored in the file name.
+fn include_targets(arg: &str, base_dir: &Path, home: &Path) -> Vec<PathBuf> {
+    let expanded = expand_tilde(arg, home);
+    let path = if expanded.is_absolute() {
+        expanded
+    } else {
+        base_dir.join(expanded)
+    };
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if !file_name.contains(['*', '?']) {
+        return vec![path];
+    }
+
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| glob_match(&file_name, &entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    matches.sort();
+    matches
+}
+
+impl OpenSshConfig {
+    /// Loads `path`, splicing in Include directives. Relative includes are
+    /// resolved against the directory of the top-level file, as OpenSSH does
+    /// for `~/.ssh/config`.
+    pub(crate) fn load(path: &Path, home: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::from_text(&text, base_dir, home)
+    }
+
+    fn from_text(text: &str, base_dir: &Path, home: &Path) -> Result<Self, String> {
+        let mut config = Self {
+            blocks: vec![HostBlock {
+                patterns: vec!["*".to_string()],
+                options: Vec::new(),
+            }],
+            home: home.to_path_buf(),
+        };
+        config.parse(text, base_dir, 0)?;
+        Ok(config)
+    }
+
+    fn parse(&mut self, text: &str, base_dir: &Path, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err("ssh config Include nesting is too deep".to_string());
+        }
+        // Options under an unsupported `Match` block must not leak into the
+        // surrounding host, so they go into a block that never matches.
+        for line in text.lines() {
+            let Some((key, value)) = split_line(line) else {
+                continue;
+            };
+            match key.as_str() {
+                "host" => self.blocks.push(HostBlock {
+                    patterns: split_args(&value),
+                    options: Vec::new(),
+                }),
+                "match" => self.blocks.push(HostBlock {
+                    patterns: Vec::new(),
+                    options: Vec::new(),
+                }),
+                "include" => {
+                    let enclosing = self.blocks.len();
+                    for arg in split_args(&value) {
+                        for target in include_targets(&arg, base_dir, &self.home) {
+                            // Missing include files are silently skipped, like OpenSSH.
+                            if let Ok(included) = std::fs::read_to_string(&target) {
+                                self.parse(&included, base_dir, depth + 1)?;
+                            }
+                        }
+                    }
+                    // Options after the Include belong to the enclosing block
+                    // again, not to the last block the included files opened.
+                    if self.blocks.len() != enclosing {
+                        let patterns = self.blocks[enclosing - 1].patterns.clone();
+                        self.blocks.push(HostBlock {
+                            patterns,
+                            options: Vec::new(),
+                        });
+                    }
+                }
+                _ => {
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.options.push((key, value));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Concrete aliases declared in `Host` lines (patterns and negations skipped).
+    pub(crate) fn aliases(&self) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for block in &self.blocks {
+            for pattern in &block.patterns {
+                if pattern.contains(['*', '?', '!']) {
+                    continue;
+                }
+                if !out.iter().any(|a| a.eq_ignore_ascii_case(pattern)) {
+                    out.push(pattern.clone());
+                }
+            }
+        }
+        out
+    }
+
+    /// Whether `alias` is declared verbatim in some `Host` line.
+    pub(crate) fn has_alias(&self, alias: &str) -> bool {
+        self.aliases().iter().any(|a| a.eq_ignore_ascii_case(alias))
+    }
+
+    /// Options for `alias`; as in OpenSSH, the first value obtained wins.
+    fn options_for(&self, alias: &str) -> HostOptions {
+        let mut opts = HostOptions::default();
+        for block in self.blocks.iter().filter(|block| block.matches(alias)) {
+            for (key, value) in &block.options {
+                let first_arg = || split_args(value).into_iter().next();
+                match key.as_str() {
+                    "hostname" if opts.host_name.is_none() => opts.host_name = first_arg(),
+                    "port" if opts.port.is_none() => {
+                        opts.port = first_arg().and_then(|p| p.parse().ok())
+                    }
+                    "user" if opts.user.is_none() => opts.user = first_arg(),
+                    "identityfile" if opts.identity_file.is_none() => {
+                        opts.identity_file = first_arg()
+                    }
+                    "proxyjump" if opts.proxy_jump.is_none() => {
+                        opts.proxy_jump = Some(value.trim().to_string())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        opts
+    }
+
+    fn expand_tokens(&self, text: &str, alias: &str, host: &str, user: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                out.push(ch);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => out.push('%'),
+                Some('h') => out.push_str(host),
+                Some('n') => out.push_str(alias),
+                Some('r') => out.push_str(user),
+                Some('d') => out.push_str(&self.home.to_string_lossy()),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+
+    /// Resolves `alias` into a connection config, including its ProxyJump chain.
+    pub(crate) fn resolve(&self, alias: &str) -> Result<SshConfig, String> {
+        self.resolve_inner(alias, None, None, 0)
+    }
+
+    fn resolve_inner(
+        &self,
+        alias: &str,
+        user_override: Option<String>,
+        port_override: Option<u16>,
+        depth: usize,
+    ) -> Result<SshConfig, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("ProxyJump chain for {alias} is too long or cyclic"));
+        }
+        let opts = self.options_for(alias);
+        let host = opts
+            .host_name
+            .as_deref()
+            .map(|h| self.expand_tokens(h, alias, alias, ""))
+            .unwrap_or_else(|| alias.to_string());
+        let user = user_override.or(opts.user).unwrap_or_default();
+        let port = port_override.or(opts.port).unwrap_or_else(default_ssh_port);
+        let identity_file = opts.identity_file.map(|file| {
+            let expanded = self.expand_tokens(&file, alias, &host, &user);
+            expand_tilde(&expanded, &self.home)
+                .to_string_lossy()
+                .to_string()
+        });
+
+        let mut jump_hosts = Vec::new();
+        if let Some(spec) = opts.proxy_jump.filter(|s| !s.eq_ignore_ascii_case("none")) {
+            for hop in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let (hop_user, hop_host, hop_port) = parse_jump_spec(hop)?;
+                let hop_cfg = self.resolve_inner(&hop_host, hop_user, hop_port, depth + 1)?;
+                // A jump host's own ProxyJump chain comes before the hop itself.
+                jump_hosts.extend(hop_cfg.jump_hosts.iter().cloned());
+                jump_hosts.push(SshJumpHost {
+                    host: hop_cfg.host,
+                    port: hop_cfg.port.unwrap_or_else(default_ssh_port),
+                    user: hop_cfg.user,
+                    auth: SshAuthMethod::Auto,
+                    identity: None,
+                    identity_file: hop_cfg.identity_file,
+                });
+            }
+        }
+
+        Ok(SshConfig {
+            host,
+            port: Some(port),
+            user,
+            auth: SshAuthMethod::Auto,
+            identity: None,
+            identity_file,
+            jump_hosts,
+            platform: None,
+        })
+    }
+}
+
+/// Parses a ProxyJump hop: `[user@]host[:port]` or `ssh://[user@]host[:port]`.
+fn parse_jump_spec(spec: &str) -> Result<(Option<String>, String, Option<u16>), String> {
+    let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+    let (user, rest) = match spec.rsplit_once('@') {
+        Some((user, rest)) => (Some(user.to_string()), rest),
+        None => (None, spec),
+    };
+    let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+        let (host, after) = bracketed
+            .split_once(']')
+            .ok_or_else(|| format!("Invalid ProxyJump host: {spec}"))?;
+        (host.to_string(), after.strip_prefix(':'))
+    } else {
+        match rest.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), Some(port)),
+            None => (rest.to_string(), None),
+        }
+    };
+    let port = port
+        .map(|p| {
+            p.parse::<u16>()
+                .map_err(|_| format!("Invalid ProxyJump port: {spec}"))
+        })
+        .transpose()?;
+    if host.is_empty() {
+        return Err(format!("Invalid ProxyJump host: {spec}"));
+    }
+    Ok((user, host, port))
+}
+
+fn home_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().home_dir().map_err(|err| format!("{err:?}"))
+}
+
+fn default_config_path(home: &Path) -> PathBuf {
+    home.join(".ssh").join("config")
+}
+
+/// Fills `cfg` from `~/.ssh/config` when its host is a declared alias.
+/// Values set explicitly on `cfg` win over the file.
+pub(crate) fn resolve_alias(app: &AppHandle, cfg: &SshConfig) -> Result<SshConfig, String> {
+    let home = home_dir(app)?;
+    let path = default_config_path(&home);
+    if !path.is_file() {
+        return Ok(cfg.clone());
+    }
+    let config = OpenSshConfig::load(&path, &home)?;
+    if !config.has_alias(cfg.host.trim()) {
+        return Ok(cfg.clone());
+    }
+
+    Ok(apply_alias(cfg, config.resolve(cfg.host.trim())?))
+}
+
+/// `cfg` with what it leaves unset taken from the alias's resolved config.
+fn apply_alias(cfg: &SshConfig, resolved: SshConfig) -> SshConfig {
+    SshConfig {
+        host: resolved.host,
+        port: cfg.port.or(resolved.port),
+        user: if cfg.user.trim().is_empty() {
+            resolved.user
+        } else {
+            cfg.user.clone()
+        },
+        auth: cfg.auth,
+        identity: cfg.identity.clone(),
+        identity_file: cfg.identity_file.clone().or(resolved.identity_file),
+        jump_hosts: if cfg.jump_hosts.is_empty() {
+            resolved.jump_hosts
+        } else {
+            cfg.jump_hosts.clone()
+        },
+        platform: cfg.platform,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn ssh_config_import(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<Vec<SshConfigHost>, String> {
+    let home = home_dir(&app)?;
+    let path = match path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(p) => expand_tilde(&p, &home),
+        None => default_config_path(&home),
+    };
+    let config = OpenSshConfig::load(&path, &home)?;
+    config
+        .aliases()
+        .into_iter()
+        .map(|alias| {
+            let ssh = config.resolve(&alias)?;
+            Ok(SshConfigHost { alias, ssh })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home() -> PathBuf {
+        PathBuf::from("/home/me")
+    }
+
+    fn parse(text: &str) -> OpenSshConfig {
+        OpenSshConfig::from_text(text, Path::new("/nonexistent"), &home()).unwrap()
+    }
+
+    /// A fresh directory for Include files.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tauri-vm-ssh-config-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn typed(host: &str, port: Option<u16>, user: &str) -> SshConfig {
+        SshConfig {
+            host: host.to_string(),
+            port,
+            user: user.to_string(),
+            auth: SshAuthMethod::Auto,
+            identity: None,
+            identity_file: None,
+            jump_hosts: Vec::new(),
+            platform: None,
+        }
+    }
+
+    #[test]
+    fn options_after_include_in_host_stay_with_that_host() {
+        let dir = temp_dir("include-in-host");
+        std::fs::write(
+            dir.join("extra.conf"),
+            "IdentityFile ~/.ssh/web_key\nHost other\n  User included\n  Port 2300\n",
+        )
+        .unwrap();
+        let text = "Host web\n  HostName web.example.com\n  Include extra.conf\n  User deploy\n  Port 2200\n";
+        let config = OpenSshConfig::from_text(text, &dir, &home()).unwrap();
+
+        let web = config.resolve("web").unwrap();
+        assert_eq!(web.host, "web.example.com");
+        assert_eq!(web.user, "deploy");
+        assert_eq!(web.port, Some(2200));
+        assert_eq!(
+            web.identity_file.as_deref(),
+            Some(home().join(".ssh/web_key").to_string_lossy().as_ref())
+        );
+
+        let other = config.resolve("other").unwrap();
+        assert_eq!(other.user, "included");
+        assert_eq!(other.port, Some(2300));
+        assert_eq!(config.aliases(), ["web", "other"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_globs_and_missing_files() {
+        let dir = temp_dir("include-glob");
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(dir.join("conf.d/a.conf"), "Host a\n  User alice\n").unwrap();
+        std::fs::write(dir.join("conf.d/b.conf"), "Host b\n  User bob\n").unwrap();
+        let text = "Include conf.d/*.conf missing.conf\nHost c\n  User carol\n";
+        let config = OpenSshConfig::from_text(text, &dir, &home()).unwrap();
+        assert_eq!(config.aliases(), ["a", "b", "c"]);
+        assert_eq!(config.resolve("b").unwrap().user, "bob");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn match_block_options_do_not_leak() {
+        let config = parse(
+            "Host a\n  HostName a.example.com\nMatch exec true\n  User mallory\n  Port 2222\nHost b\n  User bob\n",
+        );
+        let a = config.resolve("a").unwrap();
+        assert_eq!(a.host, "a.example.com");
+        assert_eq!(a.user, "");
+        assert_eq!(a.port, Some(22));
+        assert_eq!(config.resolve("b").unwrap().user, "bob");
+    }
+
+    #[test]
+    fn wildcard_and_negated_patterns() {
+        let config = parse("Host web *.lab !secret.lab\n  User labuser\nHost *\n  User fallback\n");
+        assert_eq!(config.resolve("db.lab").unwrap().user, "labuser");
+        assert_eq!(config.resolve("DB.LAB").unwrap().user, "labuser");
+        assert_eq!(config.resolve("secret.lab").unwrap().user, "fallback");
+        assert_eq!(config.resolve("elsewhere").unwrap().user, "fallback");
+        assert_eq!(config.aliases(), ["web"]);
+        assert!(config.has_alias("WEB"));
+        assert!(!config.has_alias("db.lab"));
+    }
+
+    #[test]
+    fn first_value_wins() {
+        let config = parse(
+            "Port 2100\nHost web\n  Port 2200\n  User first\nHost web\n  Port=2300\n  User second\n  HostName web.example.com\n",
+        );
+        let web = config.resolve("web").unwrap();
+        assert_eq!(web.port, Some(2100));
+        assert_eq!(web.user, "first");
+        assert_eq!(web.host, "web.example.com");
+    }
+
+    #[test]
+    fn proxy_jump_chain() {
+        let config = parse(concat!(
+            "Host outer\n  HostName outer.example.com\n  User edge\n",
+            "Host bastion\n  HostName bastion.example.com\n  User jump\n  Port 2022\n  ProxyJump outer\n",
+            "Host inner\n  HostName 10.0.0.5\n  ProxyJump bastion,admin@[fe80::1]:2222\n",
+            "Host direct\n  ProxyJump none\n",
+        ));
+        let hops = config
+            .resolve("inner")
+            .unwrap()
+            .jump_hosts
+            .into_iter()
+            .map(|hop| (hop.host, hop.port, hop.user))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hops,
+            [
+                ("outer.example.com".to_string(), 22, "edge".to_string()),
+                ("bastion.example.com".to_string(), 2022, "jump".to_string()),
+                ("fe80::1".to_string(), 2222, "admin".to_string()),
+            ]
+        );
+        assert!(config.resolve("direct").unwrap().jump_hosts.is_empty());
+    }
+
+    #[test]
+    fn cyclic_proxy_jump_is_an_error() {
+        let config = parse("Host a\n  ProxyJump b\nHost b\n  ProxyJump a\n");
+        assert!(config.resolve("a").is_err());
+    }
+
+    #[test]
+    fn parses_jump_specs() {
+        assert_eq!(
+            parse_jump_spec("ssh://ops@gw.example.com:2200").unwrap(),
+            (
+                Some("ops".to_string()),
+                "gw.example.com".to_string(),
+                Some(2200)
+            )
+        );
+        assert_eq!(
+            parse_jump_spec("gw").unwrap(),
+            (None, "gw".to_string(), None)
+        );
+        assert!(parse_jump_spec("gw:ssh").is_err());
+        assert!(parse_jump_spec("ops@").is_err());
+    }
+
+    #[test]
+    fn typed_port_wins_over_alias_port() {
+        let resolved = parse("Host web\n  HostName web.example.com\n  Port 2200\n  User deploy\n")
+            .resolve("web")
+            .unwrap();
+
+        let explicit = apply_alias(&typed("web", Some(22), ""), resolved.clone());
+        assert_eq!(explicit.port, Some(22));
+        assert_eq!(explicit.user, "deploy");
+        assert_eq!(explicit.host, "web.example.com");
+
+        let unset = apply_alias(&typed("web", None, "me"), resolved);
+        assert_eq!(unset.port, Some(2200));
+        assert_eq!(unset.user, "me");
+    }
+}
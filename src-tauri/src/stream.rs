@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::decode_remote_output;
+
+pub(crate) const EXEC_OUTPUT_EVENT: &str = "exec-output";
+
+// Lines are held back until complete so multi-byte text is never split, but a
+// chatty command without newlines still gets flushed at this size.
+const MAX_PENDING: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OutputKind {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecOutputChunk {
+    request_id: String,
+    /// Increases across every command run for the request, so the UI can
+    /// order chunks from multi-step flows such as the stop escalation.
+    seq: u64,
+    stream: OutputKind,
+    data: String,
+}
+
+/// Where a request's live output goes; cloned into every command it runs.
+#[derive(Clone)]
+pub(crate) struct OutputStream {
+    app: AppHandle,
+    request_id: String,
+    seq: Arc<AtomicU64>,
+}
+
+impl OutputStream {
+    /// Streaming is opt-in per call and needs a request id to tag chunks with.
+    pub(crate) fn for_request(
+        app: &AppHandle,
+        stream: Option<bool>,
+        request_id: &Option<String>,
+    ) -> Result<Option<Self>, String> {
+        if !stream.unwrap_or(false) {
+            return Ok(None);
+        }
+        let request_id = request_id
+            .clone()
+            .filter(|id| !id.trim().is_empty())
+            .ok_or_else(|| "Streaming output requires a request id".to_string())?;
+        Ok(Some(Self {
+            app: app.clone(),
+            request_id,
+            seq: Arc::new(AtomicU64::new(0)),
+        }))
+    }
+
+    /// Line buffers for one remote command.
+    pub(crate) fn writer(&self) -> OutputWriter {
+        OutputWriter {
+            stream: self.clone(),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    fn emit(&self, kind: OutputKind, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let _ = self.app.emit(
+            EXEC_OUTPUT_EVENT,
+            ExecOutputChunk {
+                request_id: self.request_id.clone(),
+                seq: self.seq.fetch_add(1, Ordering::Relaxed),
+                stream: kind,
+                data: decode_remote_output(bytes),
+            },
+        );
+    }
+}
+
+pub(crate) struct OutputWriter {
+    stream: OutputStream,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl OutputWriter {
+    pub(crate) fn push(&mut self, kind: OutputKind, data: &[u8]) {
+        let pending = match kind {
+            OutputKind::Stdout => &mut self.stdout,
+            OutputKind::Stderr => &mut self.stderr,
+        };
+        pending.extend_from_slice(data);
+        let cut = if pending.len() >= MAX_PENDING {
+            pending.len()
+        } else {
+            match pending.iter().rposition(|b| *b == b'\n') {
+                Some(pos) => pos + 1,
+                None => return,
+            }
+        };
+        let chunk: Vec<u8> = pending.drain(..cut).collect();
+        self.stream.emit(kind, &chunk);
+    }
+
+    pub(crate) fn finish(mut self) {
+        let stdout = std::mem::take(&mut self.stdout);
+        let stderr = std::mem::take(&mut self.stderr);
+        self.stream.emit(OutputKind::Stdout, &stdout);
+        self.stream.emit(OutputKind::Stderr, &stderr);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::known_hosts::host_id;
+use crate::SshConfig;
+
+static TIMEOUTS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Timing knobs for SSH connections and the VM start/stop flows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct TimeoutProfile {
+    /// TCP connect, handshake and authentication, per hop.
+    pub(crate) connect_timeout_secs: u64,
+    pub(crate) keepalive_interval_secs: u64,
+    /// Upper bound for a single remote command; 0 disables it.
+    pub(crate) command_deadline_secs: u64,
+    /// One-second `vmrun list` polls after a scheduled-task start.
+    pub(crate) start_poll_budget: u32,
+    /// One-second polls after a scheduled-task stop.
+    pub(crate) stop_poll_budget: u32,
+    /// One-second polls after killing `vmware-vmx.exe`.
+    pub(crate) kill_poll_budget: u32,
+    /// Tries for idempotent operations (list, status, scan); 1 disables retry.
+    pub(crate) retry_attempts: u32,
+    /// First retry backoff; doubles per retry, with jitter.
+    pub(crate) retry_base_delay_ms: u64,
+}
+
+impl Default for TimeoutProfile {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            keepalive_interval_secs: 5,
+            command_deadline_secs: 300,
+            start_poll_budget: 8,
+            stop_poll_budget: 60,
+            kill_poll_budget: 10,
+            retry_attempts: 3,
+            retry_base_delay_ms: 500,
+        }
+    }
+}
+
+impl TimeoutProfile {
+    pub(crate) fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub(crate) fn keepalive_interval(&self) -> Duration {
+        Duration::from_secs(self.keepalive_interval_secs)
+    }
+
+    pub(crate) fn command_deadline(&self) -> Option<Duration> {
+        (self.command_deadline_secs > 0).then(|| Duration::from_secs(self.command_deadline_secs))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=300).contains(&self.connect_timeout_secs) {
+            return Err("Connect timeout must be between 1 and 300 seconds".to_string());
+        }
+        if !(1..=600).contains(&self.keepalive_interval_secs) {
+            return Err("Keepalive interval must be between 1 and 600 seconds".to_string());
+        }
+        if self.command_deadline_secs > 24 * 60 * 60 {
+            return Err("Command deadline must be at most 24 hours".to_string());
+        }
+        for (label, budget) in [
+            ("Start", self.start_poll_budget),
+            ("Stop", self.stop_poll_budget),
+            ("Kill", self.kill_poll_budget),
+        ] {
+            if budget > 3600 {
+                return Err(format!("{label} poll budget must be at most 3600 polls"));
+            }
+        }
+        if !(1..=10).contains(&self.retry_attempts) {
+            return Err("Retry attempts must be between 1 and 10".to_string());
+        }
+        if self.retry_base_delay_ms > 60_000 {
+            return Err("Retry base delay must be at most 60 seconds".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Per-host overrides; unset fields fall back to the default profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct TimeoutOverrides {
+    connect_timeout_secs: Option<u64>,
+    keepalive_interval_secs: Option<u64>,
+    command_deadline_secs: Option<u64>,
+    start_poll_budget: Option<u32>,
+    stop_poll_budget: Option<u32>,
+    kill_poll_budget: Option<u32>,
+    retry_attempts: Option<u32>,
+    retry_base_delay_ms: Option<u64>,
+}
+
+impl TimeoutOverrides {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn apply(&self, base: TimeoutProfile) -> TimeoutProfile {
+        TimeoutProfile {
+            connect_timeout_secs: self
+                .connect_timeout_secs
+                .unwrap_or(base.connect_timeout_secs),
+            keepalive_interval_secs: self
+                .keepalive_interval_secs
+                .unwrap_or(base.keepalive_interval_secs),
+            command_deadline_secs: self
+                .command_deadline_secs
+                .unwrap_or(base.command_deadline_secs),
+            start_poll_budget: self.start_poll_budget.unwrap_or(base.start_poll_budget),
+            stop_poll_budget: self.stop_poll_budget.unwrap_or(base.stop_poll_budget),
+            kill_poll_budget: self.kill_poll_budget.unwrap_or(base.kill_poll_budget),
+            retry_attempts: self.retry_attempts.unwrap_or(base.retry_attempts),
+            retry_base_delay_ms: self.retry_base_delay_ms.unwrap_or(base.retry_base_delay_ms),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct TimeoutSettings {
+    defaults: TimeoutProfile,
+    /// Keyed by `host_id(host, port)`.
+    hosts: BTreeMap<String, TimeoutOverrides>,
+}
+
+fn timeouts_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("settings");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir.join("timeouts.json"))
+}
+
+fn load_timeouts(app: &AppHandle) -> Result<TimeoutSettings, String> {
+    let path = timeouts_path(app)?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(TimeoutSettings::default())
+        }
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(TimeoutSettings::default());
+    }
+    serde_json::from_str(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_timeouts(app: &AppHandle, settings: &TimeoutSettings) -> Result<(), String> {
+    let path = timeouts_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+/// Effective profile for a host: its overrides on top of the defaults.
+pub(crate) fn profile_for(
+    app: &AppHandle,
+    host: &str,
+    port: u16,
+) -> Result<TimeoutProfile, String> {
+    let _guard = TIMEOUTS_LOCK.lock().expect("timeouts lock poisoned");
+    let settings = load_timeouts(app)?;
+    Ok(match settings.hosts.get(&host_id(host, port)) {
+        Some(overrides) => overrides.apply(settings.defaults),
+        None => settings.defaults,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn timeouts_get(app: AppHandle) -> Result<TimeoutSettings, String> {
+    let _guard = TIMEOUTS_LOCK.lock().expect("timeouts lock poisoned");
+    load_timeouts(&app)
+}
+
+#[tauri::command]
+pub(crate) fn timeouts_effective(app: AppHandle, ssh: SshConfig) -> Result<TimeoutProfile, String> {
+    profile_for(&app, &ssh.host, ssh.port())
+}
+
+/// Connection settings apply to new connections; pooled sessions keep theirs.
+#[tauri::command]
+pub(crate) fn timeouts_set_defaults(app: AppHandle, profile: TimeoutProfile) -> Result<(), String> {
+    profile.validate()?;
+    let _guard = TIMEOUTS_LOCK.lock().expect("timeouts lock poisoned");
+    let mut settings = load_timeouts(&app)?;
+    settings.defaults = profile;
+    for overrides in settings.hosts.values() {
+        overrides.apply(profile).validate()?;
+    }
+    save_timeouts(&app, &settings)
+}
+
+/// Sets or, with `None` / all fields unset, clears a host's overrides.
+#[tauri::command]
+pub(crate) fn timeouts_set_host(
+    app: AppHandle,
+    host: String,
+    port: u16,
+    overrides: Option<TimeoutOverrides>,
+) -> Result<(), String> {
+    let _guard = TIMEOUTS_LOCK.lock().expect("timeouts lock poisoned");
+    let mut settings = load_timeouts(&app)?;
+    let key = host_id(&host, port);
+    match overrides.filter(|o| !o.is_empty()) {
+        Some(overrides) => {
+            overrides.apply(settings.defaults).validate()?;
+            settings.hosts.insert(key, overrides);
+        }
+        None => {
+            settings.hosts.remove(&key);
+        }
+    }
+    save_timeouts(&app, &settings)
+}
@@ -0,0 +1,325 @@
+use tauri::AppHandle;
+
+use crate::hypervisor::{self, check_uuid, GuestVm, VmProvider};
+use crate::remote_host::{sh_quote, HostPlatform};
+use crate::{
+    powershell_prelude, ps_single_quote_escape, ssh_config, timeouts, RequestContext, SshConfig,
+    TraceStore, VmStopMode,
+};
+
+/// Separates `list vms` from `list runningvms` in the listing output.
+const RUNNING_MARKER: &str = "--runningvms--";
+
+/// Script lines that leave VBoxManage's path in `$vbox`; the Windows
+/// installer does not always put it on PATH.
+fn locator(platform: HostPlatform) -> &'static str {
+    match platform {
+        HostPlatform::Windows => {
+            r#"$c=@()
+$g=Get-Command VBoxManage.exe -CommandType Application -ErrorAction SilentlyContinue|Select-Object -First 1
+if($g){ $c+=$g.Source }
+foreach($d in @($env:VBOX_MSI_INSTALL_PATH,$env:VBOX_INSTALL_PATH,(Get-ItemProperty -LiteralPath 'HKLM:\SOFTWARE\Oracle\VirtualBox' -ErrorAction SilentlyContinue).InstallDir,(Join-Path $env:ProgramFiles 'Oracle\VirtualBox'))){ if($d){ $c+=(Join-Path $d 'VBoxManage.exe') } }
+$vbox=$c|Where-Object{ Test-Path -LiteralPath $_ }|Select-Object -First 1
+if(-not $vbox){ throw 'PROVIDER_NOT_INSTALLED: VBoxManage.exe not found' }"#
+        }
+        HostPlatform::Posix => {
+            r#"vbox=$(command -v VBoxManage 2>/dev/null || command -v vboxmanage 2>/dev/null)
+if [ -z "$vbox" ]; then echo 'PROVIDER_NOT_INSTALLED: VBoxManage not found' >&2; exit 127; fi"#
+        }
+    }
+}
+
+/// `body` runs after the locator; `{vbox}` in it is the VBoxManage call.
+fn script(platform: HostPlatform, body: &str) -> String {
+    match platform {
+        HostPlatform::Windows => format!(
+            "{}\n{}\n{}",
+            powershell_prelude(),
+            locator(platform),
+            body.replace("{vbox}", "& $vbox")
+        ),
+        HostPlatform::Posix => format!(
+            "{}\n{}",
+            locator(platform),
+            body.replace("{vbox}", "\"$vbox\"")
+        ),
+    }
+}
+
+/// A VM id as a script argument.
+fn quote_id(platform: HostPlatform, id: &str) -> String {
+    match platform {
+        HostPlatform::Windows => format!("'{}'", ps_single_quote_escape(id)),
+        HostPlatform::Posix => sh_quote(id),
+    }
+}
+
+/// Exit with VBoxManage's status, which PowerShell does not do on its own.
+fn exit_status(platform: HostPlatform) -> &'static str {
+    match platform {
+        HostPlatform::Windows => "exit $LASTEXITCODE",
+        HostPlatform::Posix => "",
+    }
+}
+
+fn list_script(platform: HostPlatform) -> String {
+    let body = match platform {
+        HostPlatform::Windows => format!(
+            "{{vbox}} list vms\nif($LASTEXITCODE -ne 0){{ exit $LASTEXITCODE }}\n'{RUNNING_MARKER}'\n{{vbox}} list runningvms\nexit $LASTEXITCODE"
+        ),
+        HostPlatform::Posix => format!(
+            "{{vbox}} list vms || exit $?\necho '{RUNNING_MARKER}'\n{{vbox}} list runningvms"
+        ),
+    };
+    script(platform, &body)
+}
+
+/// One `"name" {uuid}` line of `VBoxManage list`.
+fn parse_vm_line(line: &str) -> Option<(String, String)> {
+    let (name, uuid) = line.trim().rsplit_once(" {")?;
+    let uuid = uuid.strip_suffix('}')?;
+    let name = name.trim();
+    let name = name
+        .strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .unwrap_or(name);
+    Some((name.to_string(), uuid.to_ascii_lowercase()))
+}
+
+/// VBoxManage's name for a registered VM whose settings it cannot read.
+const INACCESSIBLE: &str = "<inaccessible>";
+
+/// Reads the output of `list_script`: every registered VM, marked running
+/// when it also appears after the marker.
+pub(crate) fn parse_list_output(output: &str) -> Vec<GuestVm> {
+    let (all, running) = output.split_once(RUNNING_MARKER).unwrap_or((output, ""));
+    let running = running
+        .lines()
+        .filter_map(parse_vm_line)
+        .map(|(_, uuid)| uuid)
+        .collect::<Vec<_>>();
+    all.lines()
+        .filter_map(parse_vm_line)
+        .map(|(name, uuid)| {
+            let is_running = running.contains(&uuid);
+            let state = if name == INACCESSIBLE {
+                "inaccessible"
+            } else if is_running {
+                "running"
+            } else {
+                "stopped"
+            };
+            GuestVm {
+                provider: VmProvider::Virtualbox,
+                id: uuid,
+                name,
+                is_running,
+                state: state.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Every VM registered with VirtualBox for the SSH user, running or not.
+#[tauri::command]
+pub(crate) async fn virtualbox_list_vms(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<Vec<GuestVm>, String> {
+    hypervisor::list_command(&app, "virtualbox_list_vms", &request_id, stream, |ctx| {
+        list_vms(&app, &store, &ssh, ctx, &request_id)
+    })
+    .await
+}
+
+pub(crate) async fn list_vms(
+    app: &AppHandle,
+    store: &TraceStore,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+    request_id: &Option<String>,
+) -> Result<Vec<GuestVm>, String> {
+    let out = hypervisor::run_script_with(
+        app,
+        store,
+        ssh,
+        ctx,
+        "virtualbox_list_vms",
+        request_id,
+        true,
+        |platform| Ok(list_script(platform)),
+    )
+    .await?;
+    Ok(parse_list_output(&out))
+}
+
+#[tauri::command]
+pub(crate) async fn virtualbox_start_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "virtualbox_start_vm",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            Ok(script(
+                platform,
+                &format!(
+                    "{{vbox}} startvm {} --type headless\n{}",
+                    quote_id(platform, &vm_id),
+                    exit_status(platform)
+                ),
+            ))
+        },
+    )
+    .await
+}
+
+/// Soft presses the ACPI power button; hard pulls the plug.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn virtualbox_stop_vm(
+    app: AppHandle,
+    store: tauri::State<'_, TraceStore>,
+    ssh: SshConfig,
+    vm_id: String,
+    mode: Option<VmStopMode>,
+    request_id: Option<String>,
+    stream: Option<bool>,
+) -> Result<String, String> {
+    check_uuid("VM id", &vm_id)?;
+    let cfg = ssh_config::resolve_alias(&app, &ssh)?;
+    let polls = timeouts::profile_for(&app, &cfg.host, cfg.port())?.stop_poll_budget;
+    let mode = mode.unwrap_or(VmStopMode::Soft);
+    hypervisor::run_script(
+        &app,
+        &store,
+        &ssh,
+        "virtualbox_stop_vm",
+        request_id,
+        stream,
+        false,
+        |platform| {
+            let id = quote_id(platform, &vm_id);
+            let body = match (mode, platform) {
+                (VmStopMode::Hard, _) => format!(
+                    "{{vbox}} controlvm {id} poweroff\n{}",
+                    exit_status(platform)
+                ),
+                (VmStopMode::Soft, HostPlatform::Windows) => format!(
+                    r#"{{vbox}} controlvm {id} acpipowerbutton
+if($LASTEXITCODE -ne 0){{ exit $LASTEXITCODE }}
+for($i=0;$i -lt {polls};$i++){{
+  Start-Sleep -Seconds 1
+  if(-not (({{vbox}} list runningvms) -match {id})){{ 'VM stopped'; exit 0 }}
+}}
+[Console]::Error.WriteLine('VM is still running after ACPI shutdown')
+exit 1"#
+                ),
+                (VmStopMode::Soft, HostPlatform::Posix) => format!(
+                    r#"{{vbox}} controlvm {id} acpipowerbutton || exit $?
+i=0
+while [ $i -lt {polls} ]; do
+  sleep 1
+  {{vbox}} list runningvms | grep -qi {id} || {{ echo 'VM stopped'; exit 0; }}
+  i=$((i+1))
+done
+echo 'VM is still running after ACPI shutdown' >&2
+exit 1"#
+                ),
+            };
+            Ok(script(platform, &body))
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `list_script` on a Linux host running VirtualBox 7.0: one VM whose
+    /// name contains ` {`, one whose .vbox file was deleted.
+    const LIST_POSIX: &str = r#""ubuntu-22.04" {3f2b8c1d-6a4e-4f0b-9d7c-2e1a5b8c9d0f}
+"win10 {clone}" {a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d}
+"<inaccessible>" {7c6b5a49-3827-4165-b0a9-f8e7d6c5b4a3}
+"Alpine" {0a9b8c7d-6e5f-4a3b-2c1d-0e9f8a7b6c5d}
+--runningvms--
+"ubuntu-22.04" {3f2b8c1d-6a4e-4f0b-9d7c-2e1a5b8c9d0f}
+"win10 {clone}" {a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d}
+"#;
+
+    /// The same listing from a Windows host through PowerShell, which ends
+    /// lines with CRLF.
+    const LIST_WINDOWS: &str = "\"Win11 Dev\" {9F8E7D6C-5B4A-4392-8170-6F5E4D3C2B1A}\r\n\"Debian\" {12345678-9abc-4def-8123-456789abcdef}\r\n--runningvms--\r\n\"Win11 Dev\" {9f8e7d6c-5b4a-4392-8170-6f5e4d3c2b1a}\r\n";
+
+    #[test]
+    fn parses_list_and_running_vms() {
+        let vms = parse_list_output(LIST_POSIX);
+        let rows: Vec<_> = vms
+            .iter()
+            .map(|vm| (vm.name.as_str(), vm.is_running, vm.state.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("ubuntu-22.04", true, "running"),
+                ("win10 {clone}", true, "running"),
+                ("<inaccessible>", false, "inaccessible"),
+                ("Alpine", false, "stopped"),
+            ]
+        );
+        assert!(vms.iter().all(|vm| vm.provider == VmProvider::Virtualbox));
+        assert_eq!(vms[1].id, "a1b2c3d4-e5f6-4a7b-8c9d-0e1f2a3b4c5d");
+    }
+
+    #[test]
+    fn parses_crlf_output_and_matches_uuids_case_insensitively() {
+        let vms = parse_list_output(LIST_WINDOWS);
+        assert_eq!(vms.len(), 2);
+        assert_eq!(vms[0].name, "Win11 Dev");
+        assert_eq!(vms[0].id, "9f8e7d6c-5b4a-4392-8170-6f5e4d3c2b1a");
+        assert!(vms[0].is_running);
+        assert_eq!(vms[1].name, "Debian");
+        assert!(!vms[1].is_running);
+    }
+
+    #[test]
+    fn no_vms_or_no_marker() {
+        assert!(parse_list_output("").is_empty());
+        assert!(parse_list_output("--runningvms--\n").is_empty());
+        // Without the marker nothing counts as running.
+        let vms = parse_list_output("\"Alpine\" {0a9b8c7d-6e5f-4a3b-2c1d-0e9f8a7b6c5d}\n");
+        assert_eq!(vms.len(), 1);
+        assert!(!vms[0].is_running);
+    }
+
+    #[test]
+    fn skips_lines_that_are_not_vms() {
+        assert_eq!(
+            parse_vm_line("VBoxManage: error: Failed to create the VirtualBox object!"),
+            None
+        );
+        assert_eq!(parse_vm_line("\"broken\" {unterminated"), None);
+        assert_eq!(
+            parse_vm_line("  \"a\" {B0A9F8E7-D6C5-4B4A-8392-817060504030}  "),
+            Some((
+                "a".to_string(),
+                "b0a9f8e7-d6c5-4b4a-8392-817060504030".to_string()
+            ))
+        );
+    }
+}
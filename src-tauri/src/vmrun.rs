@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::known_hosts::host_id;
+use crate::remote_host::{self, HostCache};
+use crate::{cancel, RequestContext, SshConfig};
+
+static VMRUN_LOCK: Mutex<()> = Mutex::new(());
+
+/// The product vmrun talks to, passed as `-T`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum VmwareHostType {
+    #[default]
+    Ws,
+    Player,
+    Server,
+    Esx,
+    Vc,
+}
+
+impl VmwareHostType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            VmwareHostType::Ws => "ws",
+            VmwareHostType::Player => "player",
+            VmwareHostType::Server => "server",
+            VmwareHostType::Esx => "esx",
+            VmwareHostType::Vc => "vc",
+        }
+    }
+
+    /// Only these go through a VMware server and take `-h`/`-u`/`-p`.
+    fn is_remote(&self) -> bool {
+        matches!(
+            self,
+            VmwareHostType::Server | VmwareHostType::Esx | VmwareHostType::Vc
+        )
+    }
+}
+
+/// VMware settings for one host; unset fields are auto-detected or omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct VmrunHostSettings {
+    /// Full path to vmrun on the host; detection is skipped when set.
+    pub(crate) vmrun_path: Option<String>,
+    pub(crate) host_type: Option<VmwareHostType>,
+    /// `-h`: the server, ESXi or vCenter URL.
+    pub(crate) server: Option<String>,
+    /// `-u`
+    pub(crate) server_user: Option<String>,
+    /// `-p`; never sent back to the UI.
+    pub(crate) server_password: Option<String>,
+}
+
+impl VmrunHostSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("vmrun path", &self.vmrun_path),
+            ("Server", &self.server),
+            ("Server user", &self.server_user),
+            ("Server password", &self.server_password),
+        ];
+        for (label, value) in fields {
+            let Some(value) = value else { continue };
+            if value.trim().is_empty() {
+                return Err(format!("{label} must not be empty"));
+            }
+            // Scheduled tasks pass these inside a double-quoted argument line.
+            if value.contains('"') || value.contains('\n') || value.contains('\r') {
+                return Err(format!("{label} contains unsupported characters"));
+            }
+        }
+        let remote = self.host_type.is_some_and(|t| t.is_remote());
+        if !remote
+            && (self.server.is_some()
+                || self.server_user.is_some()
+                || self.server_password.is_some())
+        {
+            return Err(
+                "Server, user and password only apply to the server, esx and vc host types"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// The vmrun to run: explicit settings over what detection found.
+    pub(crate) fn target(&self, detected: Option<&DetectedVmrun>) -> VmrunTarget {
+        VmrunTarget {
+            path: self
+                .vmrun_path
+                .clone()
+                .or_else(|| detected.map(|d| d.path.clone()))
+                .unwrap_or_default(),
+            host_type: self
+                .host_type
+                .or_else(|| detected.map(|d| d.host_type))
+                .unwrap_or_default(),
+            server: self.server.clone(),
+            server_user: self.server_user.clone(),
+            server_password: self.server_password.clone(),
+        }
+    }
+}
+
+/// What auto-detection found on a host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DetectedVmrun {
+    pub(crate) path: String,
+    pub(crate) host_type: VmwareHostType,
+}
+
+/// A located vmrun and the connection arguments every invocation starts with.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct VmrunTarget {
+    pub(crate) path: String,
+    pub(crate) host_type: VmwareHostType,
+    pub(crate) server: Option<String>,
+    pub(crate) server_user: Option<String>,
+    pub(crate) server_password: Option<String>,
+}
+
+impl VmrunTarget {
+    /// `-T <type>` and, when set, `-h`/`-u`/`-p`, in vmrun's order.
+    pub(crate) fn connection_args(&self) -> Vec<(&'static str, &str)> {
+        let mut args = vec![("-T", self.host_type.as_str())];
+        for (flag, value) in [
+            ("-h", &self.server),
+            ("-u", &self.server_user),
+            ("-p", &self.server_password),
+        ] {
+            if let Some(value) = value {
+                args.push((flag, value.as_str()));
+            }
+        }
+        args
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct VmrunSettings {
+    /// Keyed by `host_id(host, port)`.
+    hosts: BTreeMap<String, VmrunHostSettings>,
+}
+
+/// `VmrunHostSettings` as the UI sees it: the password replaced by a flag.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VmrunHostSettingsView {
+    vmrun_path: Option<String>,
+    host_type: Option<VmwareHostType>,
+    server: Option<String>,
+    server_user: Option<String>,
+    has_server_password: bool,
+}
+
+impl From<VmrunHostSettings> for VmrunHostSettingsView {
+    fn from(settings: VmrunHostSettings) -> Self {
+        Self {
+            vmrun_path: settings.vmrun_path,
+            host_type: settings.host_type,
+            server: settings.server,
+            server_user: settings.server_user,
+            has_server_password: settings.server_password.is_some(),
+        }
+    }
+}
+
+fn vmrun_settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("{err:?}"))?
+        .join("settings");
+
+    std::fs::create_dir_all(&dir).map_err(|err| format!("{err:?}"))?;
+    Ok(dir.join("vmrun.json"))
+}
+
+fn load_vmrun_settings(app: &AppHandle) -> Result<VmrunSettings, String> {
+    let path = vmrun_settings_path(app)?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(v) => v,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(VmrunSettings::default())
+        }
+        Err(err) => return Err(format!("{err:?}")),
+    };
+    if text.trim().is_empty() {
+        return Ok(VmrunSettings::default());
+    }
+    serde_json::from_str(&text).map_err(|err| format!("{err:?}"))
+}
+
+fn save_vmrun_settings(app: &AppHandle, settings: &VmrunSettings) -> Result<(), String> {
+    let path = vmrun_settings_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|err| format!("{err:?}"))?;
+    std::fs::write(&tmp, bytes).map_err(|err| format!("{err:?}"))?;
+    std::fs::rename(&tmp, &path).map_err(|err| format!("{err:?}"))?;
+    Ok(())
+}
+
+/// A host's VMware settings; all unset when it has none.
+pub(crate) fn settings_for(
+    app: &AppHandle,
+    host: &str,
+    port: u16,
+) -> Result<VmrunHostSettings, String> {
+    let _guard = VMRUN_LOCK.lock().expect("vmrun lock poisoned");
+    Ok(load_vmrun_settings(app)?
+        .hosts
+        .remove(&host_id(host, port))
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub(crate) fn vmrun_settings_get(
+    app: AppHandle,
+) -> Result<BTreeMap<String, VmrunHostSettingsView>, String> {
+    let _guard = VMRUN_LOCK.lock().expect("vmrun lock poisoned");
+    Ok(load_vmrun_settings(&app)?
+        .hosts
+        .into_iter()
+        .map(|(key, settings)| (key, settings.into()))
+        .collect())
+}
+
+/// Sets or, with `None` / all fields unset, clears a host's settings. An
+/// unset `serverPassword` keeps the stored one; clear it by clearing `server`.
+#[tauri::command]
+pub(crate) fn vmrun_settings_set_host(
+    app: AppHandle,
+    host: String,
+    port: u16,
+    settings: Option<VmrunHostSettings>,
+) -> Result<(), String> {
+    let _guard = VMRUN_LOCK.lock().expect("vmrun lock poisoned");
+    let mut stored = load_vmrun_settings(&app)?;
+    let key = host_id(&host, port);
+    match settings {
+        Some(mut settings) => {
+            if settings.server_password.is_none() && settings.server.is_some() {
+                settings.server_password = stored
+                    .hosts
+                    .get(&key)
+                    .and_then(|old| old.server_password.clone());
+            }
+            if settings.is_empty() {
+                stored.hosts.remove(&key);
+            } else {
+                settings.validate()?;
+                stored.hosts.insert(key.clone(), settings);
+            }
+        }
+        None => {
+            stored.hosts.remove(&key);
+        }
+    }
+    save_vmrun_settings(&app, &stored)?;
+    app.state::<HostCache>().forget_vmrun(&key);
+    Ok(())
+}
+
+async fn vmrun_detect_inner(
+    app: &AppHandle,
+    ssh: &SshConfig,
+    ctx: RequestContext,
+) -> Result<DetectedVmrun, String> {
+    app.state::<HostCache>()
+        .forget_vmrun(&host_id(&ssh.host, ssh.port()));
+    remote_host::detected_vmrun(app, ssh, &ctx).await
+}
+
+/// Runs detection again, ignoring the cached result, and reports what it
+/// found. Explicit settings still take precedence when commands run.
+#[tauri::command]
+pub(crate) async fn vmrun_detect(
+    app: AppHandle,
+    ssh: SshConfig,
+    request_id: Option<String>,
+) -> Result<DetectedVmrun, String> {
+    cancel::cancellable(&app, "vmrun_detect", &request_id, |cancel| {
+        vmrun_detect_inner(
+            &app,
+            &ssh,
+            RequestContext {
+                output: None,
+                cancel,
+            },
+        )
+    })
+    .await
+}
@@ -268,10 +268,10 @@ export default function App() {
   async function refreshKeyStatus() {
     setSshKeyError("");
     try {
-      const present = await withLog("refresh_key_status", () => tauri.sshKeyStatus(), {
+      const status = await withLog("refresh_key_status", () => tauri.sshKeyStatus(), {
         meta: { ssh: summarizeSsh(ssh) },
       });
-      setSshKeyPresent(present);
+      setSshKeyPresent(status.configured || status.agentAvailable);
     } catch (err) {
       setSshKeyPresent(false);
       setSshKeyError(String(err));
@@ -318,7 +318,7 @@ export default function App() {
           summary: "hostname",
           meta: { ssh: summarizeSsh(ssh) },
         });
-      pushToast({ kind: "success", title: "连接正常", message: out.trim() || "OK" });
+      pushToast({ kind: "success", title: "连接正常", message: out.stdout.trim() || "OK" });
     } catch (err) {
       setGlobalError(String(err));
       pushToast({ kind: "error", title: "连接失败", message: String(err) });
@@ -329,12 +329,16 @@ export default function App() {
     setIsKeyWorking(true);
     setSshKeyError("");
     try {
-      await withLog("upload_key", () => tauri.sshSetPrivateKey(keyText), {
+      const upload = await withLog("upload_key", () => tauri.sshSetPrivateKey(keyText), {
         summary: "Upload SSH private key",
         meta: { keySize: keyText.length },
       });
       setSshKeyPresent(true);
-      pushToast({ kind: "success", title: "私钥已保存" });
+      if (upload.encrypted && !upload.unlocked) {
+        pushToast({ kind: "info", title: "私钥已保存", message: "该私钥已加密，连接前需要输入口令" });
+      } else {
+        pushToast({ kind: "success", title: "私钥已保存" });
+      }
     } catch (err) {
       setSshKeyPresent(false);
       setSshKeyError(String(err));
@@ -485,7 +489,7 @@ export default function App() {
         summary: "Run diagnostic command",
         meta: { ssh: summarizeSsh(ssh) },
       });
-      setDiagOutput(out);
+      setDiagOutput(out.truncated ? `${out.stdout}\n…（输出已截断）` : out.stdout);
       pushToast({ kind: "success", title: "诊断命令已执行" });
     } catch (err) {
       setDiagError(String(err));
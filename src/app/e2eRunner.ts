@@ -220,12 +220,12 @@ export async function runE2EInvokeSuite(): Promise<{ ok: boolean; events: E2EEve
     e2eLog("CONFIG", JSON.stringify({ hasVmxPath: Boolean(vmxPath), runHardStop, waitAfterStartMs, timeoutMs }));
     await runStep(events, "trace_clear", () => tauri.traceClear());
 
-    const keyPresent = await runStep(events, "ssh_key_status", () => tauri.sshKeyStatus());
-    if (!keyPresent) {
+    const keyStatus = await runStep(events, "ssh_key_status", () => tauri.sshKeyStatus());
+    if (!keyStatus.configured && !keyStatus.agentAvailable) {
       if (!keyText) throw new Error("SSH key missing; set VITE_E2E_SSH_KEY_TEXT or configure key in app first.");
       await runStep(events, "ssh_set_private_key", () => tauri.sshSetPrivateKey(keyText));
-      const presentAfter = await runStep(events, "ssh_key_status_after_set", () => tauri.sshKeyStatus());
-      if (!presentAfter) throw new Error("ssh_set_private_key reported success, but ssh_key_status is still false.");
+      const statusAfter = await runStep(events, "ssh_key_status_after_set", () => tauri.sshKeyStatus());
+      if (!statusAfter.configured) throw new Error("ssh_set_private_key reported success, but ssh_key_status is still false.");
     }
 
     await runStep(events, "ssh_exec_hostname", () =>
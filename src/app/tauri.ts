@@ -1,28 +1,502 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { SshConfig, VmPassword, VmStopMode } from "./types";
+import type { HostPlatform, SshConfig, VmPassword, VmStopMode } from "./types";
+
+export type SshKeyStatus = {
+  configured: boolean;
+  agentAvailable: boolean;
+  agentIdentities: number;
+  certificate: CertificateInfo | null;
+};
 
 export async function sshKeyStatus() {
-  return invoke<boolean>("ssh_key_status");
+  return invoke<SshKeyStatus>("ssh_key_status");
+}
+
+export const SSH_KEY_PASSPHRASE_REQUIRED = "SSH_KEY_PASSPHRASE_REQUIRED";
+export const SSH_KEY_PASSPHRASE_INVALID = "SSH_KEY_PASSPHRASE_INVALID";
+
+export type SshKeyUpload = {
+  encrypted: boolean;
+  unlocked: boolean;
+};
+
+export async function sshSetPrivateKey(keyText: string, passphrase?: string) {
+  return invoke<SshKeyUpload>("ssh_set_private_key", { keyText, passphrase });
+}
+
+export async function sshKeyUnlock(passphrase: string, identity?: string) {
+  return invoke<void>("ssh_key_unlock", { passphrase, identity });
+}
+
+export async function sshKeyLock(identity?: string) {
+  return invoke<void>("ssh_key_lock", { identity });
+}
+
+export type SshIdentity = {
+  name: string;
+  algorithm: string;
+  fingerprint: string;
+  comment: string;
+  encrypted: boolean;
+  unlocked: boolean;
+  certificate: CertificateInfo | null;
+  addedAt: number;
+};
+
+export async function sshIdentityList() {
+  return invoke<SshIdentity[]>("ssh_identity_list");
+}
+
+export async function sshIdentityAdd(name: string, keyText: string, passphrase?: string, comment?: string) {
+  return invoke<SshIdentity>("ssh_identity_add", { name, keyText, passphrase, comment });
+}
+
+export async function sshIdentityRemove(name: string) {
+  return invoke<void>("ssh_identity_remove", { name });
 }
 
-export async function sshSetPrivateKey(keyText: string) {
-  return invoke<void>("ssh_set_private_key", { keyText });
+export async function sshIdentityRename(name: string, newName: string) {
+  return invoke<SshIdentity>("ssh_identity_rename", { name, newName });
 }
 
 export async function sshClearPrivateKey() {
   return invoke<void>("ssh_clear_private_key");
 }
 
-export async function sshExec(ssh: SshConfig, command: string, requestId?: string) {
-  return invoke<string>("ssh_exec", { ssh, command, requestId });
+export const SSH_CERT_EXPIRED = "SSH_CERT_EXPIRED";
+export const SSH_CERT_NOT_YET_VALID = "SSH_CERT_NOT_YET_VALID";
+
+export type CertificateInfo = {
+  keyId: string;
+  serial: number;
+  principals: string[];
+  validAfter: number;
+  validBefore: number | null;
+  caFingerprint: string;
+  expired: boolean;
+};
+
+export async function sshCertificateSet(certText: string, identity?: string) {
+  return invoke<CertificateInfo>("ssh_certificate_set", { identity, certText });
+}
+
+export async function sshCertificateClear(identity?: string) {
+  return invoke<void>("ssh_certificate_clear", { identity });
+}
+
+export async function sshCertificateInfo(identity?: string) {
+  return invoke<CertificateInfo | null>("ssh_certificate_info", { identity });
+}
+
+export async function sshPasswordStatus(ssh: SshConfig) {
+  return invoke<boolean>("ssh_password_status", { ssh });
+}
+
+export async function sshPasswordSet(ssh: SshConfig, password: string) {
+  return invoke<void>("ssh_password_set", { ssh, password });
+}
+
+export async function sshPasswordClear(ssh: SshConfig) {
+  return invoke<void>("ssh_password_clear", { ssh });
+}
+
+export type KnownHost = {
+  hostId: string;
+  algorithm: string;
+  fingerprint: string;
+  publicKey: string;
+  addedAt: number;
+};
+
+export type HostKeyMismatch = {
+  code: "HOST_KEY_MISMATCH";
+  host: string;
+  port: number;
+  expectedFingerprint: string;
+  actualFingerprint: string;
+  actualAlgorithm: string;
+  actualPublicKey: string;
+};
+
+export const HOST_KEY_MISMATCH = "HOST_KEY_MISMATCH";
+
+export function parseHostKeyMismatch(err: unknown): HostKeyMismatch | null {
+  const message = String(err);
+  const at = message.indexOf(`${HOST_KEY_MISMATCH}:`);
+  if (at < 0) return null;
+  try {
+    return JSON.parse(message.slice(at + HOST_KEY_MISMATCH.length + 1)) as HostKeyMismatch;
+  } catch {
+    return null;
+  }
+}
+
+export async function hostKeyList() {
+  return invoke<KnownHost[]>("host_key_list");
+}
+
+export async function hostKeyApprove(host: string, port: number, publicKey: string, fingerprint: string) {
+  return invoke<KnownHost>("host_key_approve", { host, port, publicKey, fingerprint });
+}
+
+export async function hostKeyForget(host: string, port: number) {
+  return invoke<void>("host_key_forget", { host, port });
+}
+
+export type PooledSession = {
+  host: string;
+  port: number;
+  user: string;
+  inUse: boolean;
+  ageMs: number;
+  idleMs: number;
+};
+
+export async function sshPoolList() {
+  return invoke<PooledSession[]>("ssh_pool_list");
+}
+
+export async function sshPoolSetIdleTtl(idleTtlSecs: number) {
+  return invoke<void>("ssh_pool_set_idle_ttl", { idleTtlSecs });
+}
+
+export async function sshPoolClear() {
+  return invoke<void>("ssh_pool_clear");
+}
+
+export type SshConfigHost = {
+  alias: string;
+  ssh: SshConfig;
+};
+
+export async function sshConfigImport(path?: string) {
+  return invoke<SshConfigHost[]>("ssh_config_import", { path });
+}
+
+export type TimeoutProfile = {
+  connectTimeoutSecs: number;
+  keepaliveIntervalSecs: number;
+  commandDeadlineSecs: number;
+  startPollBudget: number;
+  stopPollBudget: number;
+  killPollBudget: number;
+  retryAttempts: number;
+  retryBaseDelayMs: number;
+};
+
+export type TimeoutOverrides = Partial<TimeoutProfile>;
+
+export type TimeoutSettings = {
+  defaults: TimeoutProfile;
+  hosts: Record<string, TimeoutOverrides>;
+};
+
+export async function timeoutsGet() {
+  return invoke<TimeoutSettings>("timeouts_get");
+}
+
+export async function timeoutsEffective(ssh: SshConfig) {
+  return invoke<TimeoutProfile>("timeouts_effective", { ssh });
+}
+
+export async function timeoutsSetDefaults(profile: TimeoutProfile) {
+  return invoke<void>("timeouts_set_defaults", { profile });
+}
+
+export async function timeoutsSetHost(host: string, port: number, overrides?: TimeoutOverrides) {
+  return invoke<void>("timeouts_set_host", { host, port, overrides });
+}
+
+export type VmwareHostType = "ws" | "player" | "server" | "esx" | "vc";
+
+export type VmrunHostSettings = {
+  vmrunPath?: string;
+  hostType?: VmwareHostType;
+  server?: string;
+  serverUser?: string;
+  serverPassword?: string;
+};
+
+export type VmrunHostSettingsView = {
+  vmrunPath: string | null;
+  hostType: VmwareHostType | null;
+  server: string | null;
+  serverUser: string | null;
+  hasServerPassword: boolean;
+};
+
+export type DetectedVmrun = {
+  path: string;
+  hostType: VmwareHostType;
+};
+
+export async function vmrunSettingsGet() {
+  return invoke<Record<string, VmrunHostSettingsView>>("vmrun_settings_get");
+}
+
+export async function vmrunSettingsSetHost(host: string, port: number, settings?: VmrunHostSettings) {
+  return invoke<void>("vmrun_settings_set_host", { host, port, settings });
+}
+
+export async function vmrunDetect(ssh: SshConfig, requestId?: string) {
+  return invoke<DetectedVmrun>("vmrun_detect", { ssh, requestId });
+}
+
+export type PortForward = {
+  id: number;
+  sshHost: string;
+  sshPort: number;
+  sshUser: string;
+  localAddress: string;
+  remoteHost: string;
+  remotePort: number;
+  bytesSent: number;
+  bytesReceived: number;
+  activeConnections: number;
+  totalConnections: number;
+  createdAt: number;
+};
+
+export async function portForwardOpen(
+  ssh: SshConfig,
+  bindPort: number,
+  remoteHost: string,
+  remotePort: number,
+  bindHost?: string,
+) {
+  return invoke<PortForward>("port_forward_open", {
+    ssh,
+    bindHost,
+    bindPort,
+    remoteHost,
+    remotePort,
+  });
+}
+
+export async function portForwardList() {
+  return invoke<PortForward[]>("port_forward_list");
+}
+
+export async function portForwardClose(id: number) {
+  return invoke<void>("port_forward_close", { id });
+}
+
+export type SftpEntry = {
+  path: string;
+  size: number;
+  isDir: boolean;
+  isSymlink: boolean;
+  permissions: number | null;
+  modifiedAt: number | null;
+};
+
+export type SftpTransfer = {
+  localPath: string;
+  remotePath: string;
+  size: number;
+  resumedFrom: number;
+  sha256: string;
+  durationMs: number;
+};
+
+export const SFTP_PROGRESS_EVENT = "sftp-progress";
+
+export type SftpProgress = {
+  requestId: string | null;
+  direction: "upload" | "download";
+  remotePath: string;
+  transferred: number;
+  total: number;
+  done: boolean;
+};
+
+export async function sftpStat(ssh: SshConfig, path: string) {
+  return invoke<SftpEntry>("sftp_stat", { ssh, path });
+}
+
+export async function sftpMkdir(ssh: SshConfig, path: string, recursive?: boolean) {
+  return invoke<void>("sftp_mkdir", { ssh, path, recursive });
+}
+
+export async function sftpDelete(ssh: SshConfig, path: string) {
+  return invoke<void>("sftp_delete", { ssh, path });
+}
+
+export async function sftpUpload(
+  ssh: SshConfig,
+  localPath: string,
+  remotePath: string,
+  requestId?: string,
+  resume?: boolean,
+) {
+  return invoke<SftpTransfer>("sftp_upload", { ssh, localPath, remotePath, resume, requestId });
+}
+
+export async function sftpDownload(
+  ssh: SshConfig,
+  remotePath: string,
+  localPath: string,
+  requestId?: string,
+  resume?: boolean,
+) {
+  return invoke<SftpTransfer>("sftp_download", { ssh, remotePath, localPath, resume, requestId });
+}
+
+export type ShellInfo = {
+  id: number;
+  sshHost: string;
+  sshPort: number;
+  sshUser: string;
+  term: string;
+  cols: number;
+  rows: number;
+  createdAt: number;
+};
+
+export const SHELL_OUTPUT_EVENT = "shell-output";
+export const SHELL_EXIT_EVENT = "shell-exit";
+
+export type ShellOutput = {
+  shellId: number;
+  data: string;
+};
+
+export type ShellExit = {
+  shellId: number;
+  exitStatus: number | null;
+};
+
+export async function shellOpen(ssh: SshConfig, cols?: number, rows?: number, term?: string) {
+  return invoke<ShellInfo>("shell_open", { ssh, cols, rows, term });
+}
+
+export async function shellWrite(id: number, data: string) {
+  return invoke<void>("shell_write", { id, data });
+}
+
+export async function shellResize(id: number, cols: number, rows: number) {
+  return invoke<void>("shell_resize", { id, cols, rows });
+}
+
+export async function shellList() {
+  return invoke<ShellInfo[]>("shell_list");
+}
+
+export async function shellClose(id: number) {
+  return invoke<void>("shell_close", { id });
+}
+
+export type CaptureSettings = {
+  maxInMemoryBytes: number;
+};
+
+export type OutputPage = {
+  data: string;
+  offset: number;
+  nextOffset: number;
+  totalBytes: number;
+  eof: boolean;
+};
+
+export async function captureSettingsGet() {
+  return invoke<CaptureSettings>("capture_settings_get");
+}
+
+export async function captureSettingsSet(settings: CaptureSettings) {
+  return invoke<void>("capture_settings_set", { settings });
+}
+
+export async function outputRead(outputId: string, stream: "stdout" | "stderr", offset: number, limit?: number) {
+  return invoke<OutputPage>("output_read", { outputId, stream, offset, limit });
+}
+
+export async function outputSave(outputId: string, stream: "stdout" | "stderr", localPath: string) {
+  return invoke<number>("output_save", { outputId, stream, localPath });
+}
+
+export async function outputDiscard(outputId: string) {
+  return invoke<void>("output_discard", { outputId });
+}
+
+export const EXEC_OUTPUT_EVENT = "exec-output";
+
+export type ExecOutputChunk = {
+  requestId: string;
+  seq: number;
+  stream: "stdout" | "stderr";
+  data: string;
+};
+
+export const REQUEST_CANCELLED = "REQUEST_CANCELLED";
+
+export async function cancelRequest(requestId: string) {
+  return invoke<boolean>("cancel_request", { requestId });
+}
+
+export type SshExecOutput = {
+  stdout: string;
+  stderr: string;
+  exitStatus: number | null;
+  truncated: boolean;
+  outputId: string | null;
+};
+
+export async function sshExec(ssh: SshConfig, command: string, requestId?: string, stream?: boolean) {
+  return invoke<SshExecOutput>("ssh_exec", { ssh, command, requestId, stream });
 }
 
 export async function sshDir(ssh: SshConfig) {
   return invoke<string>("ssh_dir", { ssh });
 }
 
-export async function vmwareListRunning(ssh: SshConfig, requestId?: string) {
-  return invoke<string[]>("vmware_list_running", { ssh, requestId });
+export type DiagnoseStage =
+  | "dns"
+  | "tcp"
+  | "handshake"
+  | "authMethods"
+  | "auth"
+  | "shell"
+  | "powershell"
+  | "vmrun"
+  | "scheduledTasks";
+
+export type StageStatus = "passed" | "warning" | "failed" | "skipped";
+
+export type StageResult = {
+  stage: DiagnoseStage;
+  status: StageStatus;
+  durationMs: number;
+  detail: string | null;
+  error: string | null;
+};
+
+export type HostDiagnosis = {
+  host: string;
+  port: number;
+  user: string;
+  stages: StageResult[];
+  addresses: string[];
+  banner: string | null;
+  kex: string | null;
+  cipher: string | null;
+  hostKeyAlgorithm: string | null;
+  hostKeyFingerprint: string | null;
+  authMethods: string[];
+  defaultShell: string | null;
+  platform: HostPlatform | null;
+  powershellVersion: string | null;
+  languageMode: string | null;
+  vmrunPath: string | null;
+  vmrunVersion: string | null;
+  scheduledTasks: boolean | null;
+};
+
+export async function hostDiagnose(ssh: SshConfig, requestId?: string) {
+  return invoke<HostDiagnosis>("host_diagnose", { ssh, requestId });
+}
+
+export async function vmwareListRunning(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<string[]>("vmware_list_running", { ssh, requestId, stream });
 }
 
 export type VmItem = {
@@ -30,12 +504,17 @@ export type VmItem = {
   is_running: boolean;
 };
 
-export async function vmwareStatusForKnown(ssh: SshConfig, knownVmxPaths: string[], requestId?: string) {
-  return invoke<VmItem[]>("vmware_status_for_known", { ssh, knownVmxPaths, requestId });
+export async function vmwareStatusForKnown(
+  ssh: SshConfig,
+  knownVmxPaths: string[],
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<VmItem[]>("vmware_status_for_known", { ssh, knownVmxPaths, requestId, stream });
 }
 
-export async function vmwareStartVm(ssh: SshConfig, vmxPath: string, requestId?: string) {
-  return invoke<string>("vmware_start_vm", { ssh, vmxPath, requestId });
+export async function vmwareStartVm(ssh: SshConfig, vmxPath: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("vmware_start_vm", { ssh, vmxPath, requestId, stream });
 }
 
 export async function vmwareStartVmWithPassword(
@@ -43,12 +522,13 @@ export async function vmwareStartVmWithPassword(
   vmxPath: string,
   vmPassword?: VmPassword,
   requestId?: string,
+  stream?: boolean,
 ) {
-  return invoke<string>("vmware_start_vm", { ssh, vmxPath, vmPassword, requestId });
+  return invoke<string>("vmware_start_vm", { ssh, vmxPath, vmPassword, requestId, stream });
 }
 
-export async function vmwareStartVmAuto(ssh: SshConfig, vmxPath: string, requestId?: string) {
-  return invoke<string>("vmware_start_vm_auto", { ssh, vmxPath, requestId });
+export async function vmwareStartVmAuto(ssh: SshConfig, vmxPath: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("vmware_start_vm_auto", { ssh, vmxPath, requestId, stream });
 }
 
 export async function vmwareStopVm(
@@ -57,12 +537,19 @@ export async function vmwareStopVm(
   mode?: VmStopMode,
   requestId?: string,
   vmPassword?: VmPassword,
+  stream?: boolean,
 ) {
-  return invoke<string>("vmware_stop_vm", { ssh, vmxPath, mode, requestId, vmPassword });
+  return invoke<string>("vmware_stop_vm", { ssh, vmxPath, mode, requestId, vmPassword, stream });
 }
 
-export async function vmwareStopVmAuto(ssh: SshConfig, vmxPath: string, mode?: VmStopMode, requestId?: string) {
-  return invoke<string>("vmware_stop_vm_auto", { ssh, vmxPath, mode, requestId });
+export async function vmwareStopVmAuto(
+  ssh: SshConfig,
+  vmxPath: string,
+  mode?: VmStopMode,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("vmware_stop_vm_auto", { ssh, vmxPath, mode, requestId, stream });
 }
 
 export async function vmPasswordStatus(vmxPath: string) {
@@ -77,22 +564,308 @@ export async function vmPasswordClear(vmxPath: string) {
   return invoke<void>("vm_password_clear", { vmxPath });
 }
 
-export async function vmwareScanDefaultVmx(ssh: SshConfig, requestId?: string) {
-  return invoke<string[]>("vmware_scan_default_vmx", { ssh, requestId });
+export async function vmwareScanDefaultVmx(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<string[]>("vmware_scan_default_vmx", { ssh, requestId, stream });
+}
+
+export async function vmwareScanVmx(ssh: SshConfig, roots: string[], requestId?: string, stream?: boolean) {
+  return invoke<string[]>("vmware_scan_vmx", { ssh, roots, requestId, stream });
+}
+
+export type VmProvider = "vmware" | "hyperv" | "virtualbox" | "libvirt" | "proxmox";
+
+export type GuestVm = {
+  provider: VmProvider;
+  id: string;
+  name: string;
+  isRunning: boolean;
+  state: string;
+};
+
+export type GuestSnapshot = {
+  id: string;
+  name: string;
+  parentId: string | null;
+  createdAt: number | null;
+  isCurrent: boolean;
+};
+
+export async function hypervListVms(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<GuestVm[]>("hyperv_list_vms", { ssh, requestId, stream });
+}
+
+export async function hypervStartVm(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("hyperv_start_vm", { ssh, vmId, requestId, stream });
+}
+
+export async function hypervStopVm(
+  ssh: SshConfig,
+  vmId: string,
+  mode?: VmStopMode,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("hyperv_stop_vm", { ssh, vmId, mode, requestId, stream });
+}
+
+export async function hypervCheckpointList(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<GuestSnapshot[]>("hyperv_checkpoint_list", { ssh, vmId, requestId, stream });
+}
+
+export async function hypervCheckpointCreate(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("hyperv_checkpoint_create", { ssh, vmId, name, requestId, stream });
+}
+
+export async function hypervCheckpointRestore(
+  ssh: SshConfig,
+  vmId: string,
+  checkpointId: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("hyperv_checkpoint_restore", { ssh, vmId, checkpointId, requestId, stream });
+}
+
+export async function hypervCheckpointDelete(
+  ssh: SshConfig,
+  vmId: string,
+  checkpointId: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("hyperv_checkpoint_delete", { ssh, vmId, checkpointId, requestId, stream });
+}
+
+export async function virtualboxListVms(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<GuestVm[]>("virtualbox_list_vms", { ssh, requestId, stream });
+}
+
+export async function virtualboxStartVm(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("virtualbox_start_vm", { ssh, vmId, requestId, stream });
+}
+
+export async function virtualboxStopVm(
+  ssh: SshConfig,
+  vmId: string,
+  mode?: VmStopMode,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("virtualbox_stop_vm", { ssh, vmId, mode, requestId, stream });
+}
+
+export async function libvirtListVms(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<GuestVm[]>("libvirt_list_vms", { ssh, requestId, stream });
+}
+
+export async function libvirtStartVm(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("libvirt_start_vm", { ssh, vmId, requestId, stream });
+}
+
+export async function libvirtStopVm(
+  ssh: SshConfig,
+  vmId: string,
+  mode?: VmStopMode,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("libvirt_stop_vm", { ssh, vmId, mode, requestId, stream });
+}
+
+export async function libvirtSnapshotList(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<GuestSnapshot[]>("libvirt_snapshot_list", { ssh, vmId, requestId, stream });
+}
+
+export async function libvirtSnapshotCreate(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("libvirt_snapshot_create", { ssh, vmId, name, requestId, stream });
+}
+
+export async function libvirtSnapshotRevert(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("libvirt_snapshot_revert", { ssh, vmId, name, requestId, stream });
+}
+
+export async function libvirtSnapshotDelete(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("libvirt_snapshot_delete", { ssh, vmId, name, requestId, stream });
 }
 
-export async function vmwareScanVmx(ssh: SshConfig, roots: string[], requestId?: string) {
-  return invoke<string[]>("vmware_scan_vmx", { ssh, roots, requestId });
+export type LibvirtDisk = {
+  device: string;
+  target: string | null;
+  source: string | null;
+};
+
+export type LibvirtInterface = {
+  kind: string;
+  mac: string | null;
+  source: string | null;
+  model: string | null;
+};
+
+export type LibvirtDomainInfo = {
+  uuid: string;
+  name: string;
+  title: string | null;
+  description: string | null;
+  memoryKib: number | null;
+  vcpus: number | null;
+  osType: string | null;
+  arch: string | null;
+  machine: string | null;
+  disks: LibvirtDisk[];
+  interfaces: LibvirtInterface[];
+};
+
+export async function libvirtVmInfo(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<LibvirtDomainInfo>("libvirt_vm_info", { ssh, vmId, requestId, stream });
 }
 
+export type ProxmoxVm = {
+  vmid: number;
+  name: string;
+  status: string;
+  memMb: number;
+  bootdiskGb: number;
+  pid: number | null;
+};
+
+export type ProxmoxVmStatus = {
+  vmid: number | null;
+  name: string | null;
+  status: string;
+  qmpstatus: string | null;
+  uptimeSecs: number | null;
+  cpus: number | null;
+  memBytes: number | null;
+  maxmemBytes: number | null;
+  pid: number | null;
+  lock: string | null;
+};
+
+export const SUDO_PASSWORD_REQUIRED = "SUDO_PASSWORD_REQUIRED";
+
+export async function proxmoxListVms(ssh: SshConfig, requestId?: string, stream?: boolean) {
+  return invoke<ProxmoxVm[]>("proxmox_list_vms", { ssh, requestId, stream });
+}
+
+export async function proxmoxVmStatus(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<ProxmoxVmStatus>("proxmox_vm_status", { ssh, vmId, requestId, stream });
+}
+
+export async function proxmoxStartVm(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<string>("proxmox_start_vm", { ssh, vmId, requestId, stream });
+}
+
+export async function proxmoxStopVm(
+  ssh: SshConfig,
+  vmId: string,
+  mode?: VmStopMode,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("proxmox_stop_vm", { ssh, vmId, mode, requestId, stream });
+}
+
+export async function proxmoxSnapshotList(ssh: SshConfig, vmId: string, requestId?: string, stream?: boolean) {
+  return invoke<GuestSnapshot[]>("proxmox_snapshot_list", { ssh, vmId, requestId, stream });
+}
+
+export async function proxmoxSnapshotCreate(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("proxmox_snapshot_create", { ssh, vmId, name, requestId, stream });
+}
+
+export async function proxmoxSnapshotRollback(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("proxmox_snapshot_rollback", { ssh, vmId, name, requestId, stream });
+}
+
+export async function proxmoxSnapshotDelete(
+  ssh: SshConfig,
+  vmId: string,
+  name: string,
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<string>("proxmox_snapshot_delete", { ssh, vmId, name, requestId, stream });
+}
+
+export type ProviderError = {
+  provider: VmProvider;
+  error: string;
+};
+
+export type VmStatusAll = {
+  vms: GuestVm[];
+  errors: ProviderError[];
+};
+
+export async function vmStatusAll(
+  ssh: SshConfig,
+  knownVmxPaths: string[],
+  requestId?: string,
+  stream?: boolean,
+) {
+  return invoke<VmStatusAll>("vm_status_all", { ssh, knownVmxPaths, requestId, stream });
+}
+
+export const SSH_TRANSIENT = "SSH_TRANSIENT";
+
+export type RetryAttempt = {
+  attempt: number;
+  durationMs: number;
+  error: string | null;
+  backoffMs: number | null;
+};
+
 export type TraceEntry = {
   id: number;
   at: number;
   action: string;
   ok: boolean;
+  cancelled: boolean;
   durationMs: number;
   command: string;
-  output: string;
+  stdout: string;
+  stderr: string;
+  exitStatus: number | null;
+  exitSignal: string | null;
+  truncated: boolean;
+  outputId: string | null;
+  attempts: RetryAttempt[];
   error?: string | null;
   requestId?: string | null;
 };
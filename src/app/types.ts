@@ -1,7 +1,25 @@
+export type SshAuthMethod = "auto" | "public-key" | "agent" | "password" | "keyboard-interactive";
+
+export type HostPlatform = "windows" | "posix";
+
+export type SshJumpHost = {
+  host: string;
+  port: number;
+  user: string;
+  auth?: SshAuthMethod;
+  identity?: string;
+  identityFile?: string;
+};
+
 export type SshConfig = {
   host: string;
   port: number;
   user: string;
+  auth?: SshAuthMethod;
+  identity?: string;
+  identityFile?: string;
+  jumpHosts?: SshJumpHost[];
+  platform?: HostPlatform;
 };
 
 export type KnownVm = {
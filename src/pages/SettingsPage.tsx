@@ -119,6 +119,8 @@ export function SettingsPage(props: {
             action: trace.action,
             ok: trace.ok,
             durationMs: trace.durationMs,
+            exitStatus: trace.exitStatus,
+            exitSignal: trace.exitSignal,
             error: trace.error,
             requestId: trace.requestId,
           },
@@ -129,8 +131,11 @@ export function SettingsPage(props: {
         "Command",
         trace.command || "(empty)",
         "",
-        "Output",
-        trace.output || "(empty)",
+        "Stdout",
+        trace.stdout || "(empty)",
+        "",
+        "Stderr",
+        trace.stderr || "(empty)",
       );
     } else {
       lines.push("", "Trace", "(no command trace data)");
@@ -438,11 +443,19 @@ export function SettingsPage(props: {
                           </pre>
                         </div>
                         <div className="rounded-2xl border border-slate-900/10 bg-white/70 p-3 dark:border-slate-700/60 dark:bg-slate-900/60">
-                          <p className="m-0 text-sm font-semibold">Output</p>
+                          <p className="m-0 text-sm font-semibold">Stdout</p>
                           <pre className={`m-0 mt-1 whitespace-pre-wrap break-words font-mono text-sm ${ui.muted}`}>
-                            {traceMap.get(e.requestId)?.output || "(empty)"}
+                            {traceMap.get(e.requestId)?.stdout || "(empty)"}
                           </pre>
                         </div>
+                        {traceMap.get(e.requestId)?.stderr ? (
+                          <div className="rounded-2xl border border-slate-900/10 bg-white/70 p-3 dark:border-slate-700/60 dark:bg-slate-900/60">
+                            <p className="m-0 text-sm font-semibold">Stderr</p>
+                            <pre className={`m-0 mt-1 whitespace-pre-wrap break-words font-mono text-sm ${ui.muted}`}>
+                              {traceMap.get(e.requestId)?.stderr}
+                            </pre>
+                          </div>
+                        ) : null}
                       </>
                     ) : (
                       <p className={`m-0 text-sm ${ui.muted}`}>暂无命令追踪数据。</p>